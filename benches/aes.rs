@@ -0,0 +1,196 @@
+//! Throughput of ECB and CBC with a cipher keyed once (`aes128::Aes128`),
+//! next to keying a new one for every block like they used to, and of the
+//! `_in_place` versions that don't allocate
+//!
+//! The last section is the shape of a padding oracle attack: lots of
+//! decryptions of a two-block message with the same key, where allocating
+//! is most of the work. "keyed per call" is `cbc_decrypt_in_place` with a
+//! `Key`, which keys a new cipher every time; the rows after it hold on to
+//! one, which is what an attack loop should do.
+//!
+//! `cargo bench --bench aes` runs it properly, `cargo bench --bench aes
+//! --features soft-aes` does the same for the from-scratch backend. Under
+//! `cargo test` the inputs are kept tiny so it only checks that everything
+//! still runs.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use ez_cryptopals::aes128;
+use ez_cryptopals::aes128::{Aes128, Iv, Key, Padding};
+use ez_cryptopals::random::OsRandom;
+
+fn main() {
+    // `cargo bench` passes `--bench`. `cargo test` does not
+    let benching = std::env::args().any(|arg| arg == "--bench");
+
+    let (size, rounds) = if benching { (1 << 20, 20) } else { (1 << 10, 1) };
+
+    let key = Key::from_bytes(*b"YELLOW SUBMARINE");
+    let iv = Iv::from_bytes([0; 16]);
+
+    let msg = (0..size).map(|i| (i * 7 + 3) as u8).collect::<Vec<u8>>();
+    let ecb_cipher_text = aes128::ecb_encrypt(&key, &msg);
+    let cbc_cipher_text = aes128::cbc_encrypt(&key, &iv, &msg);
+
+    let cipher = Aes128::with_key(&key);
+
+    // The in place versions work on a copy, so that every round starts from
+    // the same input. Copying is cheap next to encrypting
+    let mut buffer = vec![0u8; size + 16];
+
+    println!("{} bytes, {} rounds", size, rounds);
+
+    println!("\necb_encrypt");
+    report("per block", size, rounds, || baseline::ecb_encrypt(&key, black_box(&msg)).len());
+    report("keyed once", size, rounds, || aes128::ecb_encrypt(&key, black_box(&msg)).len());
+    report("in place", size, rounds, || {
+        buffer[..size].copy_from_slice(&msg);
+        aes128::ecb_encrypt_in_place_with(&cipher, Padding::Pkcs7, black_box(&mut buffer), size, &mut OsRandom).len()
+    });
+
+    println!("\necb_decrypt");
+    report("per block", size, rounds, || baseline::ecb_decrypt(&key, black_box(&ecb_cipher_text)).len());
+    report("keyed once", size, rounds, || aes128::ecb_decrypt(&key, black_box(&ecb_cipher_text)).unwrap().len());
+    report("in place", size, rounds, || {
+        buffer.copy_from_slice(&ecb_cipher_text);
+        aes128::ecb_decrypt_in_place_with(&cipher, Padding::Pkcs7, black_box(&mut buffer)).unwrap().len()
+    });
+
+    println!("\ncbc_encrypt");
+    report("per block", size, rounds, || baseline::cbc_encrypt(&key, &iv, black_box(&msg)).len());
+    report("keyed once", size, rounds, || aes128::cbc_encrypt(&key, &iv, black_box(&msg)).len());
+    report("in place", size, rounds, || {
+        buffer[..size].copy_from_slice(&msg);
+        aes128::cbc_encrypt_in_place_with(&cipher, iv.as_bytes(), Padding::Pkcs7, black_box(&mut buffer), size, &mut OsRandom).len()
+    });
+
+    println!("\ncbc_decrypt");
+    report("per block", size, rounds, || baseline::cbc_decrypt(&key, &iv, black_box(&cbc_cipher_text)).len());
+    report("keyed once", size, rounds, || aes128::cbc_decrypt(&key, &iv, black_box(&cbc_cipher_text)).unwrap().len());
+    report("in place", size, rounds, || {
+        buffer.copy_from_slice(&cbc_cipher_text);
+        aes128::cbc_decrypt_in_place_with(&cipher, iv.as_bytes(), Padding::Pkcs7, black_box(&mut buffer)).unwrap().len()
+    });
+
+    // Many small messages instead of one big one
+    let short_cipher_text = aes128::cbc_encrypt(&key, &iv, b"ICE ICE BABY ICE ICE BABY");
+    let messages = if benching { 1 << 16 } else { 1 << 4 };
+    let total = short_cipher_text.len() * messages;
+
+    println!("\ncbc_decrypt, {} messages of {} bytes", messages, short_cipher_text.len());
+    report("xor_bytes", total, rounds, || {
+        (0..messages).map(|_| baseline::cbc_decrypt_allocating(&cipher, iv.as_bytes(), black_box(&short_cipher_text)).len()).sum()
+    });
+    report("keyed per call", total, rounds, || {
+        let mut block = [0u8; 32];
+
+        (0..messages).map(|_| {
+            block.copy_from_slice(&short_cipher_text);
+            aes128::cbc_decrypt_in_place(&key, &iv, black_box(&mut block)).unwrap().len()
+        }).sum()
+    });
+    report("keyed once", total, rounds, || {
+        (0..messages).map(|_| {
+            aes128::cbc_decrypt_with(&cipher, iv.as_bytes(), Padding::Pkcs7, black_box(&short_cipher_text)).unwrap().len()
+        }).sum()
+    });
+    report("in place", total, rounds, || {
+        let mut block = [0u8; 32];
+
+        (0..messages).map(|_| {
+            block.copy_from_slice(&short_cipher_text);
+            aes128::cbc_decrypt_in_place_with(&cipher, iv.as_bytes(), Padding::Pkcs7, black_box(&mut block)).unwrap().len()
+        }).sum()
+    });
+}
+
+/// Run `f` `rounds` times and print how many MB/s of `size` bytes of input
+/// that works out to. `f` returns something that depends on its output, so
+/// that it can't be optimized away
+fn report<F: FnMut() -> usize>(name: &str, size: usize, rounds: u32, mut f: F) {
+    // One round to warm up
+    black_box(f());
+
+    let start = Instant::now();
+
+    for _ in 0..rounds {
+        black_box(f());
+    }
+
+    let elapsed = start.elapsed().max(Duration::from_nanos(1));
+    let megabytes = (size as f64 * rounds as f64) / (1024.0 * 1024.0);
+
+    println!("  {:<14} {:>10.1} MB/s", name, megabytes / elapsed.as_secs_f64());
+}
+
+/// ECB and CBC as they were before `Aes128` held on to its key schedule:
+/// every block goes through the free `encrypt_block`/`decrypt_block`, which
+/// key a fresh cipher each time. Also CBC decryption as it was before the
+/// in place versions, with a `Vec` for every block
+mod baseline {
+    use ez_cryptopals::aes128;
+    use ez_cryptopals::aes128::{Aes128, Iv, Key, Padding};
+    use ez_cryptopals::block_cipher;
+    use ez_cryptopals::block_cipher::BlockCipher;
+    use ez_cryptopals::random::OsRandom;
+    use ez_cryptopals::set01::challenge02;
+
+    /// The old `Aes128`, which only kept the key around
+    pub struct PerBlockAes128 {
+        key: Key,
+    }
+
+    impl BlockCipher for PerBlockAes128 {
+        const BLOCK_SIZE: usize = 16;
+        type Key = [u8; 16];
+        type Block = [u8; 16];
+
+        fn new(key: &[u8; 16]) -> Self {
+            PerBlockAes128 { key: Key::from_bytes(*key) }
+        }
+
+        fn encrypt_block(&self, block: &mut [u8; 16]) {
+            *block = aes128::encrypt_block(&self.key, block);
+        }
+
+        fn decrypt_block(&self, block: &mut [u8; 16]) {
+            *block = aes128::decrypt_block(&self.key, block);
+        }
+    }
+
+    pub fn ecb_encrypt(key: &Key, msg: &[u8]) -> Vec<u8> {
+        aes128::ecb_encrypt_with(&PerBlockAes128::new(key.as_bytes()), Padding::Pkcs7, msg, &mut OsRandom)
+    }
+
+    pub fn ecb_decrypt(key: &Key, msg: &[u8]) -> Vec<u8> {
+        aes128::ecb_decrypt_with(&PerBlockAes128::new(key.as_bytes()), Padding::Pkcs7, msg).unwrap()
+    }
+
+    pub fn cbc_encrypt(key: &Key, iv: &Iv, msg: &[u8]) -> Vec<u8> {
+        aes128::cbc_encrypt_with(&PerBlockAes128::new(key.as_bytes()), iv.as_bytes(), Padding::Pkcs7, msg, &mut OsRandom)
+    }
+
+    pub fn cbc_decrypt(key: &Key, iv: &Iv, msg: &[u8]) -> Vec<u8> {
+        aes128::cbc_decrypt_with(&PerBlockAes128::new(key.as_bytes()), iv.as_bytes(), Padding::Pkcs7, msg).unwrap()
+    }
+
+    pub fn cbc_decrypt_allocating(cipher: &Aes128, iv: &[u8; 16], msg: &[u8]) -> Vec<u8> {
+        let mut plain_text = Vec::with_capacity(msg.len());
+        let mut prev_cipher_text = *iv;
+
+        for block in msg.chunks(16) {
+            let block = block_cipher::to_block::<Aes128>(block);
+
+            let mut intermediate_block = block;
+            cipher.decrypt_block(&mut intermediate_block);
+
+            plain_text.extend_from_slice(&challenge02::xor_bytes(&intermediate_block, &prev_cipher_text));
+            prev_cipher_text = block;
+        }
+
+        Padding::Pkcs7.unpad(16, &mut plain_text).unwrap();
+
+        plain_text
+    }
+}
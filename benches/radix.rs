@@ -0,0 +1,238 @@
+//! Throughput of the radix conversions, next to the implementation they
+//! replaced
+//!
+//! `cargo bench --bench radix` runs it properly. Under `cargo test` the
+//! inputs are kept tiny so it only checks that everything still runs.
+//!
+//! There is no benchmarking crate here, so this is just `Instant` and
+//! `black_box`. Good enough to see an order of magnitude.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use ez_cryptopals::radix;
+
+fn main() {
+    // `cargo bench` passes `--bench`. `cargo test` does not
+    let benching = std::env::args().any(|arg| arg == "--bench");
+
+    let (size, rounds) = if benching { (1 << 20, 50) } else { (1 << 10, 1) };
+
+    let bytes = (0..size).map(|i| (i * 7 + 3) as u8).collect::<Vec<u8>>();
+    let base16 = radix::bytes_to_base16(&bytes);
+    let base64 = radix::bytes_to_base64(&bytes);
+
+    println!("{} bytes, {} rounds", size, rounds);
+
+    println!("\nbytes_to_base16");
+    report("baseline", size, rounds, || baseline::bytes_to_base16(black_box(&bytes)).len());
+    report("table", size, rounds, || radix::bytes_to_base16(black_box(&bytes)).len());
+
+    let mut out = vec![0u8; base16.len()];
+    report("table, _into", size, rounds, || {
+        radix::bytes_to_base16_into(black_box(&bytes), &mut out)
+    });
+
+    println!("\nbase16_to_bytes");
+    report("baseline", size, rounds, || baseline::base16_to_bytes(black_box(&base16)).len());
+    report("table", size, rounds, || radix::base16_to_bytes(black_box(&base16)).len());
+
+    let mut out = vec![0u8; bytes.len()];
+    report("table, _into", size, rounds, || {
+        radix::try_base16_to_bytes_into(black_box(base16.as_bytes()), &mut out).unwrap()
+    });
+
+    println!("\nbytes_to_base64");
+    report("baseline", size, rounds, || baseline::bytes_to_base64(black_box(&bytes)).len());
+    report("table", size, rounds, || radix::bytes_to_base64(black_box(&bytes)).len());
+
+    let mut out = vec![0u8; base64.len()];
+    report("table, _into", size, rounds, || {
+        radix::bytes_to_base64_into(black_box(&bytes), radix::Base64Config::STANDARD, &mut out)
+    });
+
+    println!("\nbase64_to_bytes");
+    report("baseline", size, rounds, || baseline::base64_to_bytes(black_box(&base64)).len());
+    report("table", size, rounds, || radix::base64_to_bytes(black_box(&base64)).len());
+
+    let mut out = vec![0u8; radix::max_base64_decoded_len(base64.len())];
+    report("table, _into", size, rounds, || {
+        radix::try_base64_to_bytes_into(black_box(base64.as_bytes()), radix::Base64Config::STANDARD, &mut out)
+            .unwrap()
+    });
+}
+
+/// Run `f` `rounds` times and print how many MB/s of `size` bytes of input
+/// that works out to. `f` returns something that depends on its output, so
+/// that it can't be optimized away
+fn report<F: FnMut() -> usize>(name: &str, size: usize, rounds: u32, mut f: F) {
+    // One round to warm up
+    black_box(f());
+
+    let start = Instant::now();
+
+    for _ in 0..rounds {
+        black_box(f());
+    }
+
+    let elapsed = start.elapsed().max(Duration::from_nanos(1));
+    let megabytes = (size as f64 * rounds as f64) / (1024.0 * 1024.0);
+
+    println!("  {:<14} {:>10.1} MB/s", name, megabytes / elapsed.as_secs_f64());
+}
+
+/// The radix conversions as they were before the lookup tables, standard
+/// alphabet only
+mod baseline {
+    pub fn base16_to_bytes(base16_str: &str) -> Vec<u8> {
+        let input = base16_str.as_bytes();
+        let shift = input.len() % 2;
+
+        let mut bytes = Vec::with_capacity(input.len() / 2 + shift);
+
+        let mut high = if shift == 1 { Some(0) } else { None };
+
+        for base16_char in input {
+            let tetrad = base16_to_tetrad(base16_char);
+
+            match high.take() {
+                Some(high) => bytes.push(high << 4 | tetrad),
+                None => high = Some(tetrad),
+            }
+        }
+
+        bytes
+    }
+
+    pub fn base64_to_bytes(base64_str: &str) -> Vec<u8> {
+        let sextets = base64_str.as_bytes()
+                .iter()
+                .enumerate()
+                .map(|(offset, base64_char)| (offset, base64_to_sextet(base64_char)))
+                .collect::<Vec<(usize, Option<u8>)>>();
+
+        assert!(sextets.len() % 4 == 0, "error: invalid base64 length");
+
+        let last_quantum = (sextets.len() / 4).saturating_sub(1);
+
+        let mut bytes = Vec::with_capacity(sextets.len() / 4 * 3);
+
+        for (index, quantum) in sextets.chunks(4).enumerate() {
+            match *quantum {
+                [(_, Some(first)), (_, Some(second)), (_, Some(third)), (_, Some(fourth))] => {
+                    bytes.push((first << 2) | ((second & 0b0011_0000) >> 4));
+                    bytes.push(((second & 0b0000_1111) << 4) | ((third & 0b0011_1100) >> 2));
+                    bytes.push(((third & 0b0000_0011) << 6) | fourth);
+                },
+                [(_, Some(first)), (_, Some(second)), (_, Some(third)), (_, None)] if index == last_quantum => {
+                    assert!(third & 0b0000_0011 == 0, "error: non-canonical base64");
+
+                    bytes.push((first << 2) | ((second & 0b0011_0000) >> 4));
+                    bytes.push(((second & 0b0000_1111) << 4) | ((third & 0b0011_1100) >> 2));
+                },
+                [(_, Some(first)), (_, Some(second)), (_, None), (_, None)] if index == last_quantum => {
+                    assert!(second & 0b0000_1111 == 0, "error: non-canonical base64");
+
+                    bytes.push((first << 2) | ((second & 0b0011_0000) >> 4));
+                },
+                _ => panic!("error: misplaced base64 padding"),
+            }
+        }
+
+        bytes
+    }
+
+    pub fn bytes_to_base16(bytes: &[u8]) -> String {
+        let ascii_bytes = bytes.iter()
+                                .flat_map(|byte| vec![(byte & 0b1111_0000) >> 4, byte & 0b0000_1111])
+                                .map(|tetrad| tetrad_to_base16(&tetrad))
+                                .collect::<Vec<u8>>();
+
+        String::from_utf8(ascii_bytes).unwrap()
+    }
+
+    pub fn bytes_to_base64(bytes: &[u8]) -> String {
+        let base64_ascii_characters = bytes.chunks(3)
+                .flat_map(|bytes| {
+                    if bytes.len() == 3 {
+                        let first_sextet = bytes[0] >> 2;
+                        let second_sextet = ((bytes[0] & 0b0000_0011) << 4) | ((bytes[1] & 0b1111_0000) >> 4);
+                        let third_sextet = ((bytes[1] & 0b0000_1111) << 2) | ((bytes[2] & 0b1100_0000) >> 6);
+                        let fourth_sextet = bytes[2] & 0b0011_1111;
+
+                        vec![
+                            sextet_to_base64(&first_sextet),
+                            sextet_to_base64(&second_sextet),
+                            sextet_to_base64(&third_sextet),
+                            sextet_to_base64(&fourth_sextet)
+                        ]
+                    }
+                    else if bytes.len() == 2 {
+                        let first_sextet = bytes[0] >> 2;
+                        let second_sextet = ((bytes[0] & 0b0000_0011) << 4) | ((bytes[1] & 0b1111_0000) >> 4);
+                        let third_sextet = (bytes[1] & 0b0000_1111) << 2;
+
+                        vec![
+                            sextet_to_base64(&first_sextet),
+                            sextet_to_base64(&second_sextet),
+                            sextet_to_base64(&third_sextet),
+                            b'=',
+                        ]
+                    }
+                    else {
+                        let first_sextet = bytes[0] >> 2;
+                        let second_sextet = (bytes[0] & 0b0000_0011) << 4;
+
+                        vec![
+                            sextet_to_base64(&first_sextet),
+                            sextet_to_base64(&second_sextet),
+                            b'=',
+                            b'=',
+                        ]
+                    }
+                })
+                .collect::<Vec<u8>>();
+
+        String::from_utf8(base64_ascii_characters).unwrap()
+    }
+
+    fn base16_to_tetrad(base16_char: &u8) -> u8 {
+        match base16_char {
+            b'0'..=b'9' => base16_char - b'0',
+            b'A'..=b'F' => base16_char - b'A' + 10,
+            b'a'..=b'f' => base16_char - b'a' + 10,
+            _ => panic!("error: invalid base16 character"),
+        }
+    }
+
+    fn tetrad_to_base16(num: &u8) -> u8 {
+        match num {
+            0..=9 => b'0' + num,
+            10..=15 => b'a' + num - 10,
+            _ => panic!("error: `num` is not a 4-bit number: {}", num),
+        }
+    }
+
+    fn base64_to_sextet(base64_char: &u8) -> Option<u8> {
+        match base64_char {
+            b'0'..=b'9' => Some(base64_char - b'0' + 52),
+            b'A'..=b'Z' => Some(base64_char - b'A'),
+            b'a'..=b'z' => Some(base64_char - b'a' + 26),
+            b'+' => Some(62),
+            b'/' => Some(63),
+            b'=' => None,
+            _ => panic!("error: invalid base64 character"),
+        }
+    }
+
+    fn sextet_to_base64(num: &u8) -> u8 {
+        match num {
+            0..=25 => b'A' + num,
+            26..=51 => b'a' + num - 26,
+            52..=61 => b'0' + num - 52,
+            62 => b'+',
+            63 => b'/',
+            _ => panic!("error: `num` is not a 6-bit number: {}", num),
+        }
+    }
+}
@@ -0,0 +1,770 @@
+//! # Known answer tests in NIST's CAVP format
+//!
+//! NIST's Cryptographic Algorithm Validation Program hands out its AES test
+//! vectors as `.rsp` response files (`ECBGFSbox128.rsp`, `CBCVarKey256.rsp`,
+//! `CBCMMT128.rsp` and so on). They look like this:
+//!
+//! ```text
+//! # Comments
+//!
+//! [ENCRYPT]
+//!
+//! COUNT = 0
+//! KEY = 00000000000000000000000000000000
+//! IV = 00000000000000000000000000000000
+//! PLAINTEXT = f34481ec3cc627bacd5dc3fb08f273e6
+//! CIPHERTEXT = 0336763e966d92595a567cc9ce537f5e
+//!
+//! [DECRYPT]
+//!
+//! COUNT = 0
+//! ...
+//! ```
+//!
+//! `parse_rsp` reads one into `Vector`s, and `check_vector` runs a vector
+//! through every function here that does its mode (`encrypt_block`, the
+//! `ecb_*` and `cbc_*` families, CFB and OFB) and hands back a `Failure` for
+//! each one that got it wrong. Every vector is checked in both directions,
+//! whichever section it is in.
+//!
+//! The files themselves are in `src/aes128/input/cavp`, and the tests below
+//! run all of them. A new file there gets picked up by its name.
+
+use std::convert::TryFrom;
+use std::error;
+use std::fmt;
+
+use crate::aes128;
+use crate::aes128::{Aes128, Aes192, Aes256, CipherMode, Iv, Key, Padding, BLOCK_SIZE};
+use crate::block_cipher::BlockCipher;
+use crate::radix;
+use crate::radix::{DecodeError, HexMode};
+use crate::random::OsRandom;
+
+/// Which section of the file a vector is in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Encrypt,
+    Decrypt,
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Direction::Encrypt => write!(f, "ENCRYPT"),
+            Direction::Decrypt => write!(f, "DECRYPT"),
+        }
+    }
+}
+
+/// One `COUNT = n` and the fields that go with it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Vector {
+    pub direction: Direction,
+    pub count: usize,
+    pub key: Vec<u8>,
+
+    /// Not there for ECB
+    pub iv: Option<Vec<u8>>,
+
+    pub plain_text: Vec<u8>,
+    pub cipher_text: Vec<u8>,
+
+    /// The byte offset of the `COUNT` line, to find the vector again
+    pub offset: usize,
+}
+
+/// The reasons parsing a response file can fail
+///
+/// Like `radix::DecodeError`, every variant carries a byte offset into the
+/// input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RspError {
+    /// The vector at `offset` comes before any `[ENCRYPT]` or `[DECRYPT]`
+    MissingSection { offset: usize },
+
+    /// The section header at `offset` is not `[ENCRYPT]` or `[DECRYPT]`
+    UnknownSection { offset: usize },
+
+    /// The line at `offset` is not `NAME = value` with a name this knows, or
+    /// is a field outside of any vector
+    InvalidLine { offset: usize },
+
+    /// The `COUNT` at `offset` is not a number
+    InvalidCount { offset: usize },
+
+    /// The vector at `offset` has `field` more than once
+    DuplicateField { field: &'static str, offset: usize },
+
+    /// The vector at `offset` has no `field`
+    MissingField { field: &'static str, offset: usize },
+
+    /// A value is not hex. The offset in the `DecodeError` points into the
+    /// whole input, not just the value.
+    InvalidHex(DecodeError),
+}
+
+impl RspError {
+    /// The byte offset into the input where parsing failed
+    pub fn offset(&self) -> usize {
+        match *self {
+            RspError::MissingSection { offset } => offset,
+            RspError::UnknownSection { offset } => offset,
+            RspError::InvalidLine { offset } => offset,
+            RspError::InvalidCount { offset } => offset,
+            RspError::DuplicateField { offset, .. } => offset,
+            RspError::MissingField { offset, .. } => offset,
+            RspError::InvalidHex(e) => e.offset(),
+        }
+    }
+}
+
+impl fmt::Display for RspError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RspError::MissingSection { offset } => {
+                write!(f, "vector at offset {} is not in an [ENCRYPT] or [DECRYPT] section", offset)
+            },
+            RspError::UnknownSection { offset } => {
+                write!(f, "unknown section at offset {}", offset)
+            },
+            RspError::InvalidLine { offset } => {
+                write!(f, "invalid line at offset {}", offset)
+            },
+            RspError::InvalidCount { offset } => {
+                write!(f, "invalid COUNT at offset {}", offset)
+            },
+            RspError::DuplicateField { field, offset } => {
+                write!(f, "vector at offset {} has more than one {}", offset, field)
+            },
+            RspError::MissingField { field, offset } => {
+                write!(f, "vector at offset {} has no {}", offset, field)
+            },
+            RspError::InvalidHex(e) => write!(f, "invalid hex - {}", e),
+        }
+    }
+}
+
+impl error::Error for RspError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            RspError::InvalidHex(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// A function that got a vector wrong
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Failure {
+    pub direction: Direction,
+    pub count: usize,
+
+    /// Where the vector is, like `Vector::offset`
+    pub offset: usize,
+
+    /// The function that got it wrong, e.g. `"cbc_decrypt_with"`
+    pub function: &'static str,
+
+    pub expected: Vec<u8>,
+
+    /// Empty if the function returned an error instead
+    pub actual: Vec<u8>,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[{}] COUNT = {} at offset {}: {} gave {}, expected {}",
+            self.direction,
+            self.count,
+            self.offset,
+            self.function,
+            radix::bytes_to_base16(&self.actual),
+            radix::bytes_to_base16(&self.expected)
+        )
+    }
+}
+
+/// Parse all the vectors in a response file
+///
+/// # Panics
+///
+/// Will panic if the file is malformed. See `try_parse_rsp`
+pub fn parse_rsp(input: &str) -> Vec<Vector> {
+    try_parse_rsp(input)
+        .unwrap_or_else(|e| panic!("error: invalid response file - {}", e))
+}
+
+/// Parse all the vectors in a response file, failing with an `RspError`
+/// instead of panicking
+///
+/// Comments (`#`) and blank lines are skipped. A vector goes from its
+/// `COUNT` line to the next `COUNT` or section header, and needs a `KEY`, a
+/// `PLAINTEXT` and a `CIPHERTEXT`, in any order. `IV` is optional.
+pub fn try_parse_rsp(input: &str) -> Result<Vec<Vector>, RspError> {
+    let mut vectors = Vec::new();
+
+    let mut direction = None;
+    let mut pending: Option<PendingVector> = None;
+
+    let mut offset = 0;
+
+    for line in input.split('\n') {
+        let line_offset = offset;
+        offset += line.len() + 1;
+
+        let line = line.trim_end();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if line.starts_with('[') {
+            if let Some(vector) = pending.take() {
+                vectors.push(vector.finish()?);
+            }
+
+            direction = match line {
+                "[ENCRYPT]" => Some(Direction::Encrypt),
+                "[DECRYPT]" => Some(Direction::Decrypt),
+                _ => return Err(RspError::UnknownSection { offset: line_offset }),
+            };
+
+            continue;
+        }
+
+        let (name, value) = line.split_once('=')
+                                .ok_or(RspError::InvalidLine { offset: line_offset })?;
+
+        // Where the value starts, so that hex errors point at the right byte
+        let value_offset = line_offset + name.len() + 1 + (value.len() - value.trim_start().len());
+        let value = value.trim();
+
+        if name.trim() == "COUNT" {
+            if let Some(vector) = pending.take() {
+                vectors.push(vector.finish()?);
+            }
+
+            let direction = direction.ok_or(RspError::MissingSection { offset: line_offset })?;
+
+            let count = value.parse()
+                             .map_err(|_| RspError::InvalidCount { offset: line_offset })?;
+
+            pending = Some(PendingVector::new(direction, count, line_offset));
+
+            continue;
+        }
+
+        let vector = pending.as_mut().ok_or(RspError::InvalidLine { offset: line_offset })?;
+
+        let (field, slot) = match name.trim() {
+            "KEY" => ("KEY", &mut vector.key),
+            "IV" => ("IV", &mut vector.iv),
+            "PLAINTEXT" => ("PLAINTEXT", &mut vector.plain_text),
+            "CIPHERTEXT" => ("CIPHERTEXT", &mut vector.cipher_text),
+            _ => return Err(RspError::InvalidLine { offset: line_offset }),
+        };
+
+        if slot.is_some() {
+            return Err(RspError::DuplicateField { field, offset: vector.offset });
+        }
+
+        *slot = Some(
+            radix::try_parse_hex(value, HexMode::Strict)
+                .map_err(|e| RspError::InvalidHex(e.map_offset(|position| value_offset + position)))?
+        );
+    }
+
+    if let Some(vector) = pending.take() {
+        vectors.push(vector.finish()?);
+    }
+
+    Ok(vectors)
+}
+
+/// Run `vector` through every function that does `mode`, with whichever
+/// key size it has
+///
+/// Returns one `Failure` per function that got it wrong, so nothing at all
+/// means the vector passed.
+///
+/// # Panics
+///
+/// Will panic if there are no checks for `mode` (CTR and GCM have their own
+/// file formats), or if the vector's key or IV is the wrong length for AES
+pub fn check_vector(mode: &CipherMode, vector: &Vector) -> Vec<Failure> {
+    let mut failures = Vec::new();
+
+    match vector.key.len() {
+        16 => {
+            let key = <[u8; 16]>::try_from(&vector.key[..]).unwrap();
+
+            check_aes128_functions(&key, mode, vector, &mut failures);
+            check_with(&Aes128::new(&key), mode, vector, &mut failures);
+        },
+        24 => {
+            let key = <[u8; 24]>::try_from(&vector.key[..]).unwrap();
+
+            check_with(&Aes192::new(&key), mode, vector, &mut failures);
+        },
+        32 => {
+            let key = <[u8; 32]>::try_from(&vector.key[..]).unwrap();
+
+            check_with(&Aes256::new(&key), mode, vector, &mut failures);
+        },
+        length => {
+            panic!("error: COUNT = {} at offset {} has a {}-byte key", vector.count, vector.offset, length);
+        },
+    }
+
+    failures
+}
+
+/// Run every vector through `check_vector`
+///
+/// # Panics
+///
+/// See `check_vector`
+pub fn check_vectors(mode: &CipherMode, vectors: &[Vector]) -> Vec<Failure> {
+    vectors.iter()
+           .flat_map(|vector| check_vector(mode, vector))
+           .collect()
+}
+
+/// The `_with` functions, which work with any key size
+fn check_with<C: BlockCipher<Block = [u8; BLOCK_SIZE]>>(
+    cipher: &C,
+    mode: &CipherMode,
+    vector: &Vector,
+    failures: &mut Vec<Failure>)
+{
+    let plain_text = &vector.plain_text;
+    let cipher_text = &vector.cipher_text;
+
+    let mut expect = |function, expected: &[u8], actual: Vec<u8>| {
+        expect_eq(failures, vector, function, expected, actual)
+    };
+
+    // Errors (there shouldn't be any, without padding) show up as getting
+    // nothing back
+    match mode {
+        CipherMode::ECB => {
+            expect(
+                "ecb_encrypt_with",
+                cipher_text,
+                aes128::ecb_encrypt_with(cipher, Padding::None, plain_text, &mut OsRandom)
+            );
+            expect(
+                "ecb_decrypt_with",
+                plain_text,
+                aes128::ecb_decrypt_with(cipher, Padding::None, cipher_text).unwrap_or_default()
+            );
+
+            let mut buffer = plain_text.clone();
+            expect(
+                "ecb_encrypt_in_place_with",
+                cipher_text,
+                aes128::ecb_encrypt_in_place_with(cipher, Padding::None, &mut buffer, plain_text.len(), &mut OsRandom).to_vec()
+            );
+
+            let mut buffer = cipher_text.clone();
+            expect(
+                "ecb_decrypt_in_place_with",
+                plain_text,
+                aes128::ecb_decrypt_in_place_with(cipher, Padding::None, &mut buffer)
+                    .map(|plain_text| plain_text.to_vec())
+                    .unwrap_or_default()
+            );
+        },
+        CipherMode::CBC => {
+            let iv = iv_block(vector);
+
+            expect(
+                "cbc_encrypt_with",
+                cipher_text,
+                aes128::cbc_encrypt_with(cipher, &iv, Padding::None, plain_text, &mut OsRandom)
+            );
+            expect(
+                "cbc_decrypt_with",
+                plain_text,
+                aes128::cbc_decrypt_with(cipher, &iv, Padding::None, cipher_text).unwrap_or_default()
+            );
+
+            let mut buffer = plain_text.clone();
+            expect(
+                "cbc_encrypt_in_place_with",
+                cipher_text,
+                aes128::cbc_encrypt_in_place_with(cipher, &iv, Padding::None, &mut buffer, plain_text.len(), &mut OsRandom).to_vec()
+            );
+
+            let mut buffer = cipher_text.clone();
+            expect(
+                "cbc_decrypt_in_place_with",
+                plain_text,
+                aes128::cbc_decrypt_in_place_with(cipher, &iv, Padding::None, &mut buffer)
+                    .map(|plain_text| plain_text.to_vec())
+                    .unwrap_or_default()
+            );
+        },
+        CipherMode::CFB128 => {
+            let iv = iv_block(vector);
+
+            expect("cfb128_encrypt_with", cipher_text, aes128::cfb128_encrypt_with(cipher, &iv, plain_text));
+            expect("cfb128_decrypt_with", plain_text, aes128::cfb128_decrypt_with(cipher, &iv, cipher_text));
+        },
+        CipherMode::CFB8 => {
+            let iv = iv_block(vector);
+
+            expect("cfb8_encrypt_with", cipher_text, aes128::cfb8_encrypt_with(cipher, &iv, plain_text));
+            expect("cfb8_decrypt_with", plain_text, aes128::cfb8_decrypt_with(cipher, &iv, cipher_text));
+        },
+        CipherMode::OFB => {
+            let iv = iv_block(vector);
+
+            expect("ofb_apply_with", cipher_text, aes128::ofb_apply_with(cipher, &iv, plain_text));
+            expect("ofb_apply_with", plain_text, aes128::ofb_apply_with(cipher, &iv, cipher_text));
+        },
+        CipherMode::CTR | CipherMode::GCM => {
+            panic!("error: there are no CAVP checks for {:?} yet", mode);
+        },
+    }
+}
+
+/// The functions that only do AES-128: `encrypt_block`, `decrypt_block`,
+/// and the ones that take a `Key`
+fn check_aes128_functions(
+    key: &[u8; BLOCK_SIZE],
+    mode: &CipherMode,
+    vector: &Vector,
+    failures: &mut Vec<Failure>)
+{
+    let plain_text = &vector.plain_text;
+    let cipher_text = &vector.cipher_text;
+
+    let mut expect = |function, expected: &[u8], actual: Vec<u8>| {
+        expect_eq(failures, vector, function, expected, actual)
+    };
+
+    let aes_key = Key::from_bytes(*key);
+
+    match mode {
+        CipherMode::ECB => {
+            // Block by block, like ECB but with a fresh key schedule each time
+            expect(
+                "encrypt_block",
+                cipher_text,
+                plain_text.chunks(BLOCK_SIZE)
+                          .flat_map(|block| aes128::encrypt_block(&aes_key, &to_block(block)))
+                          .collect()
+            );
+            expect(
+                "decrypt_block",
+                plain_text,
+                cipher_text.chunks(BLOCK_SIZE)
+                           .flat_map(|block| aes128::decrypt_block(&aes_key, &to_block(block)))
+                           .collect()
+            );
+
+            // The plain version always pads, so there's an extra block at the
+            // end of the cipher text
+            let mut actual = aes128::ecb_encrypt(&aes_key, plain_text);
+            actual.truncate(cipher_text.len());
+            expect("ecb_encrypt", cipher_text, actual);
+        },
+        CipherMode::CBC => {
+            let mut actual = aes128::cbc_encrypt(&aes_key, &Iv::from_bytes(iv_block(vector)), plain_text);
+            actual.truncate(cipher_text.len());
+            expect("cbc_encrypt", cipher_text, actual);
+        },
+        CipherMode::CFB128 => {
+            let iv = Iv::from_bytes(iv_block(vector));
+
+            expect("cfb128_encrypt", cipher_text, aes128::cfb128_encrypt(&aes_key, &iv, plain_text));
+            expect("cfb128_decrypt", plain_text, aes128::cfb128_decrypt(&aes_key, &iv, cipher_text));
+        },
+        CipherMode::CFB8 => {
+            let iv = Iv::from_bytes(iv_block(vector));
+
+            expect("cfb8_encrypt", cipher_text, aes128::cfb8_encrypt(&aes_key, &iv, plain_text));
+            expect("cfb8_decrypt", plain_text, aes128::cfb8_decrypt(&aes_key, &iv, cipher_text));
+        },
+        CipherMode::OFB => {
+            let iv = Iv::from_bytes(iv_block(vector));
+
+            expect("ofb_encrypt", cipher_text, aes128::ofb_encrypt(&aes_key, &iv, plain_text));
+            expect("ofb_decrypt", plain_text, aes128::ofb_decrypt(&aes_key, &iv, cipher_text));
+        },
+        // `check_with` complains about these
+        CipherMode::CTR | CipherMode::GCM => {},
+    }
+}
+
+/// Add a `Failure` to `failures` unless `actual` is what was expected
+fn expect_eq(
+    failures: &mut Vec<Failure>,
+    vector: &Vector,
+    function: &'static str,
+    expected: &[u8],
+    actual: Vec<u8>)
+{
+    if expected != &actual[..] {
+        failures.push(Failure {
+            direction: vector.direction,
+            count: vector.count,
+            offset: vector.offset,
+            function,
+            expected: expected.to_vec(),
+            actual,
+        });
+    }
+}
+
+/// The vector's IV, as a block
+///
+/// # Panics
+///
+/// Will panic if it doesn't have one, or it is not a block long
+fn iv_block(vector: &Vector) -> [u8; BLOCK_SIZE] {
+    vector.iv.as_deref()
+             .and_then(|iv| <[u8; BLOCK_SIZE]>::try_from(iv).ok())
+             .unwrap_or_else(|| {
+                 panic!("error: COUNT = {} at offset {} needs a 16-byte IV", vector.count, vector.offset)
+             })
+}
+
+/// A block of the message. CAVP messages are whole blocks, except for
+/// CFB-8, which never gets here
+///
+/// # Panics
+///
+/// Will panic if `block` is short
+fn to_block(block: &[u8]) -> [u8; BLOCK_SIZE] {
+    <[u8; BLOCK_SIZE]>::try_from(block)
+        .unwrap_or_else(|_| panic!("error: ECB needs whole blocks, not {} bytes", block.len()))
+}
+
+/// A vector that is still being read
+struct PendingVector {
+    direction: Direction,
+    count: usize,
+    offset: usize,
+    key: Option<Vec<u8>>,
+    iv: Option<Vec<u8>>,
+    plain_text: Option<Vec<u8>>,
+    cipher_text: Option<Vec<u8>>,
+}
+
+impl PendingVector {
+    fn new(direction: Direction, count: usize, offset: usize) -> Self {
+        PendingVector {
+            direction,
+            count,
+            offset,
+            key: None,
+            iv: None,
+            plain_text: None,
+            cipher_text: None,
+        }
+    }
+
+    /// The finished `Vector`, if nothing is missing
+    fn finish(self) -> Result<Vector, RspError> {
+        let offset = self.offset;
+        let missing = |field| RspError::MissingField { field, offset };
+
+        Ok(Vector {
+            direction: self.direction,
+            count: self.count,
+            key: self.key.ok_or_else(|| missing("KEY"))?,
+            iv: self.iv,
+            plain_text: self.plain_text.ok_or_else(|| missing("PLAINTEXT"))?,
+            cipher_text: self.cipher_text.ok_or_else(|| missing("CIPHERTEXT"))?,
+            offset,
+        })
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use std::fs;
+    use std::path;
+
+    use crate::aes128::cavp;
+    use crate::aes128::cavp::{Direction, Failure, RspError, Vector};
+    use crate::aes128::CipherMode;
+    use crate::radix;
+    use crate::radix::DecodeError;
+
+    /// The mode a CAVP file is for, from the start of its name
+    fn mode_of(file_name: &str) -> CipherMode {
+        // CFB128 and CFB8 before anything shorter that they start with
+        vec![
+            ("CFB128", CipherMode::CFB128),
+            ("CFB8", CipherMode::CFB8),
+            ("ECB", CipherMode::ECB),
+            ("CBC", CipherMode::CBC),
+            ("OFB", CipherMode::OFB),
+        ]
+            .into_iter()
+            .find(|(prefix, _)| file_name.starts_with(prefix))
+            .map(|(_, mode)| mode)
+            .unwrap_or_else(|| panic!("error: no mode for {}", file_name))
+    }
+
+    #[test]
+    fn test_cavp_files() {
+        let mut paths = fs::read_dir(path::PathBuf::from("./src/aes128/input/cavp"))
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == "rsp"))
+            .collect::<Vec<path::PathBuf>>();
+        paths.sort();
+
+        assert!(!paths.is_empty());
+
+        let mut failures = Vec::new();
+
+        for path in paths {
+            let file_name = path.file_name().unwrap().to_str().unwrap().to_string();
+
+            let vectors = cavp::parse_rsp(&fs::read_to_string(&path).unwrap());
+            assert!(!vectors.is_empty(), "{} has no vectors", file_name);
+
+            for failure in cavp::check_vectors(&mode_of(&file_name), &vectors) {
+                failures.push(format!("{}: {}", file_name, failure));
+            }
+        }
+
+        assert!(failures.is_empty(), "{} failures:\n{}", failures.len(), failures.join("\n"));
+    }
+
+    #[test]
+    fn test_parse_rsp() {
+        let input = "\
+# CAVS 11.1
+# AESVS GFSbox test data for CBC
+
+[ENCRYPT]
+
+COUNT = 0
+KEY = 00000000000000000000000000000000
+IV = 00000000000000000000000000000000
+PLAINTEXT = f34481ec3cc627bacd5dc3fb08f273e6
+CIPHERTEXT = 0336763e966d92595a567cc9ce537f5e
+
+[DECRYPT]
+
+COUNT = 0
+KEY = 00000000000000000000000000000000
+IV = 00000000000000000000000000000000
+CIPHERTEXT = 0336763e966d92595a567cc9ce537f5e
+PLAINTEXT = f34481ec3cc627bacd5dc3fb08f273e6
+";
+
+        let vectors = cavp::parse_rsp(input);
+
+        assert_eq!(2, vectors.len());
+        assert_eq!(
+            Vector {
+                direction: Direction::Encrypt,
+                count: 0,
+                key: vec![0; 16],
+                iv: Some(vec![0; 16]),
+                plain_text: radix::base16_to_bytes("f34481ec3cc627bacd5dc3fb08f273e6"),
+                cipher_text: radix::base16_to_bytes("0336763e966d92595a567cc9ce537f5e"),
+                offset: input.find("COUNT").unwrap(),
+            },
+            vectors[0]
+        );
+        assert_eq!(Direction::Decrypt, vectors[1].direction);
+        assert_eq!(vectors[0].plain_text, vectors[1].plain_text);
+
+        assert!(cavp::check_vectors(&CipherMode::CBC, &vectors).is_empty());
+
+        // Without an IV, the same thing is ECB
+        let vectors = cavp::parse_rsp(&input.replace("IV = 00000000000000000000000000000000\n", ""));
+        assert_eq!(None, vectors[0].iv);
+        assert!(cavp::check_vectors(&CipherMode::ECB, &vectors).is_empty());
+    }
+
+    #[test]
+    fn test_parse_rsp_errors() {
+        assert_eq!(
+            Err(RspError::MissingSection { offset: 0 }),
+            cavp::try_parse_rsp("COUNT = 0\n")
+        );
+        assert_eq!(
+            Err(RspError::UnknownSection { offset: 1 }),
+            cavp::try_parse_rsp("\n[KEYLEN = 128]\n")
+        );
+        assert_eq!(
+            Err(RspError::InvalidLine { offset: 10 }),
+            cavp::try_parse_rsp("[ENCRYPT]\nKEY = 00\n")
+        );
+        assert_eq!(
+            Err(RspError::InvalidCount { offset: 10 }),
+            cavp::try_parse_rsp("[ENCRYPT]\nCOUNT = one\n")
+        );
+        assert_eq!(
+            Err(RspError::DuplicateField { field: "KEY", offset: 10 }),
+            cavp::try_parse_rsp("[ENCRYPT]\nCOUNT = 0\nKEY = 00\nKEY = 00\n")
+        );
+        assert_eq!(
+            Err(RspError::MissingField { field: "CIPHERTEXT", offset: 10 }),
+            cavp::try_parse_rsp("[ENCRYPT]\nCOUNT = 0\nKEY = 00\nPLAINTEXT = 00\n\nCOUNT = 1\n")
+        );
+
+        // The offset points at the bad digit in the whole file
+        let error = cavp::try_parse_rsp("[ENCRYPT]\nCOUNT = 0\nKEY = 0g\n").unwrap_err();
+        assert_eq!(RspError::InvalidHex(DecodeError::InvalidCharacter { byte: b'g', offset: 27 }), error);
+        assert_eq!(27, error.offset());
+    }
+
+    #[test]
+    fn test_reports_each_failure() {
+        let input = "\
+[ENCRYPT]
+
+COUNT = 0
+KEY = 00000000000000000000000000000000
+PLAINTEXT = f34481ec3cc627bacd5dc3fb08f273e6
+CIPHERTEXT = 0336763e966d92595a567cc9ce537f5e
+
+COUNT = 1
+KEY = 00000000000000000000000000000000
+PLAINTEXT = 9798c4640bad75c7c3227db910174e72
+CIPHERTEXT = 00000000000000000000000000000000
+";
+
+        let failures = cavp::check_vectors(&CipherMode::ECB, &cavp::parse_rsp(input));
+
+        // Only the second vector is wrong, but every function got it wrong
+        assert!(!failures.is_empty());
+        assert!(failures.iter().all(|failure| failure.count == 1));
+        assert!(failures.iter().any(|failure| failure.function == "encrypt_block"));
+        assert!(failures.iter().any(|failure| failure.function == "ecb_decrypt_in_place_with"));
+
+        let failure = failures.iter().find(|failure| failure.function == "ecb_encrypt_with").unwrap();
+        assert_eq!(
+            &Failure {
+                direction: Direction::Encrypt,
+                count: 1,
+                offset: input.rfind("COUNT").unwrap(),
+                function: "ecb_encrypt_with",
+                expected: vec![0; 16],
+                actual: radix::base16_to_bytes("a9a1631bf4996954ebc093957b234589"),
+            },
+            failure
+        );
+        assert_eq!(
+            format!(
+                "[ENCRYPT] COUNT = 1 at offset {}: ecb_encrypt_with gave a9a1631bf4996954ebc093957b234589, \
+                 expected 00000000000000000000000000000000",
+                input.rfind("COUNT").unwrap()
+            ),
+            failure.to_string()
+        );
+    }
+}
@@ -0,0 +1,458 @@
+//! # GCM: authenticated encryption
+//!
+//! None of the other modes can tell if the cipher text was tampered with.
+//! GCM (Galois/Counter Mode, NIST SP 800-38D) can. It is two things glued
+//! together:
+//! - CTR mode for the encryption itself, with the 96-bit nonce/32-bit
+//!   big-endian counter layout
+//! - GHASH, a polynomial hash over the associated data and the cipher text,
+//!   keyed with `H = E(K, 0)`. Encrypting the hash with the first counter
+//!   block gives the tag
+//!
+//! GHASH is just multiplication and addition in GF(2^128). Given the same
+//! `H`, the tag is a polynomial in `H` with the data as coefficients. That
+//! is why reusing a nonce is so bad: XOR two tags under the same key and
+//! nonce and the encrypted counter block cancels out, leaving a polynomial
+//! whose roots include `H`. `ghash` and `gf128_mul` are public so that can
+//! be played with.
+
+use std::error;
+use std::fmt;
+
+use crate::aes128::{Aes128, BLOCK_SIZE, Ctr, CounterLayout, Key, Nonce};
+use crate::aes128::mac;
+use crate::block_cipher::BlockCipher;
+
+/// How long a GCM tag is. Truncated tags are not supported
+pub const TAG_SIZE: usize = 16;
+
+/// What can go wrong when decrypting with GCM
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GcmError {
+    /// The tag doesn't match the cipher text and associated data. Either was
+    /// tampered with, or the key or IV is wrong
+    TagMismatch,
+}
+
+impl fmt::Display for GcmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            GcmError::TagMismatch => write!(f, "authentication tag mismatch"),
+        }
+    }
+}
+
+impl error::Error for GcmError {}
+
+/// Encrypt and authenticate a message with AES-GCM
+///
+/// `aad` (associated data) is authenticated but not encrypted. Returns the
+/// cipher text, which is as long as `plain_text`, and the tag.
+///
+/// # Panics
+///
+/// Will panic if `iv` is empty
+pub fn gcm_encrypt(
+    key: &Key,
+    iv: &Nonce,
+    aad: &[u8],
+    plain_text: &[u8])
+    -> (Vec<u8>, [u8; TAG_SIZE])
+{
+    gcm_encrypt_with(&Aes128::with_key(key), iv.as_bytes(), aad, plain_text)
+}
+
+/// Encrypt and authenticate a message with any block cipher with 16-byte
+/// blocks in GCM
+///
+/// # Panics
+///
+/// Will panic if `iv` is empty
+pub fn gcm_encrypt_with<C: BlockCipher<Block = [u8; BLOCK_SIZE]>>(
+    cipher: &C,
+    iv: &[u8],
+    aad: &[u8],
+    plain_text: &[u8])
+    -> (Vec<u8>, [u8; TAG_SIZE])
+{
+    let h = hash_key(cipher);
+    let mut ctr = counter(cipher, &h, iv);
+    let start = ctr.position();
+
+    // The message is encrypted starting at the block after the pre-counter
+    // block, which is saved for the tag
+    let mut cipher_text = plain_text.to_vec();
+    ctr.seek(start + BLOCK_SIZE as u64);
+    ctr.apply(&mut cipher_text);
+
+    ctr.seek(start);
+    let tag = tag(&mut ctr, &h, aad, &cipher_text);
+
+    (cipher_text, tag)
+}
+
+/// Check and decrypt a message with AES-GCM
+///
+/// Fails closed: if the tag doesn't match, no plain text comes back at all.
+///
+/// # Panics
+///
+/// Will panic if `iv` is empty
+pub fn gcm_decrypt(
+    key: &Key,
+    iv: &Nonce,
+    aad: &[u8],
+    cipher_text: &[u8],
+    tag: &[u8; TAG_SIZE])
+    -> Result<Vec<u8>, GcmError>
+{
+    gcm_decrypt_with(&Aes128::with_key(key), iv.as_bytes(), aad, cipher_text, tag)
+}
+
+/// Check and decrypt a message with any block cipher with 16-byte blocks in
+/// GCM
+///
+/// # Panics
+///
+/// Will panic if `iv` is empty
+pub fn gcm_decrypt_with<C: BlockCipher<Block = [u8; BLOCK_SIZE]>>(
+    cipher: &C,
+    iv: &[u8],
+    aad: &[u8],
+    cipher_text: &[u8],
+    expected_tag: &[u8; TAG_SIZE])
+    -> Result<Vec<u8>, GcmError>
+{
+    let h = hash_key(cipher);
+    let mut ctr = counter(cipher, &h, iv);
+    let start = ctr.position();
+
+    // Check the tag before decrypting anything
+    let tag = tag(&mut ctr, &h, aad, cipher_text);
+
+    if !mac::constant_time_eq(&tag, expected_tag) {
+        return Err(GcmError::TagMismatch);
+    }
+
+    let mut plain_text = cipher_text.to_vec();
+    ctr.seek(start + BLOCK_SIZE as u64);
+    ctr.apply(&mut plain_text);
+
+    Ok(plain_text)
+}
+
+/// The GHASH key, `H`: the encryption of a block of zeros
+pub fn hash_key<C: BlockCipher<Block = [u8; BLOCK_SIZE]>>(cipher: &C) -> [u8; BLOCK_SIZE] {
+    let mut h = [0u8; BLOCK_SIZE];
+
+    cipher.encrypt_block(&mut h);
+
+    h
+}
+
+/// GHASH of the associated data and cipher text, as GCM does it: each is
+/// zero-padded to a whole number of blocks, and a last block holds both of
+/// their lengths in bits
+///
+/// Every block is added (XORed) to the running hash, which is then
+/// multiplied by `h`.
+pub fn ghash(h: &[u8; BLOCK_SIZE], aad: &[u8], cipher_text: &[u8]) -> [u8; BLOCK_SIZE] {
+    let mut hash = [0u8; BLOCK_SIZE];
+
+    ghash_update(&mut hash, h, aad);
+    ghash_update(&mut hash, h, cipher_text);
+
+    let mut lengths = [0u8; BLOCK_SIZE];
+    lengths[..8].copy_from_slice(&(aad.len() as u64 * 8).to_be_bytes());
+    lengths[8..].copy_from_slice(&(cipher_text.len() as u64 * 8).to_be_bytes());
+
+    ghash_update(&mut hash, h, &lengths);
+
+    hash
+}
+
+/// Multiply two elements of GF(2^128), the way GCM represents them
+/// (SP 800-38D §6.3)
+///
+/// The bits are backwards from what you'd expect: the first bit of the first
+/// byte is the coefficient of x^0, the last bit of the last byte that of
+/// x^127. The field is reduced by x^128 + x^7 + x^2 + x + 1.
+///
+/// This goes through all 128 bits of `x` no matter what they are, and uses
+/// masks rather than branches.
+pub fn gf128_mul(x: &[u8; BLOCK_SIZE], y: &[u8; BLOCK_SIZE]) -> [u8; BLOCK_SIZE] {
+    // x^128 = x^7 + x^2 + x + 1, in the backwards bit order
+    const R: u128 = 0xe1 << 120;
+
+    let x = u128::from_be_bytes(*x);
+    let mut v = u128::from_be_bytes(*y);
+    let mut z = 0u128;
+
+    for i in (0..128).rev() {
+        // All ones if bit i of x is set (counting from the least
+        // significant end of the u128, i.e. from the highest power)
+        let x_bit = 0u128.wrapping_sub((x >> i) & 1);
+        z ^= v & x_bit;
+
+        // Multiply v by x. The top power falls off the (u128's) bottom, and
+        // comes back in as R
+        let carry = 0u128.wrapping_sub(v & 1);
+        v = (v >> 1) ^ (R & carry);
+    }
+
+    z.to_be_bytes()
+}
+
+/// Add `data`, zero-padded to whole blocks, to a running GHASH
+fn ghash_update(hash: &mut [u8; BLOCK_SIZE], h: &[u8; BLOCK_SIZE], data: &[u8]) {
+    for block in data.chunks(BLOCK_SIZE) {
+        for (hash_byte, byte) in hash.iter_mut().zip(block.iter()) {
+            *hash_byte ^= byte;
+        }
+
+        *hash = gf128_mul(hash, h);
+    }
+}
+
+/// A CTR keystream positioned at the pre-counter block, `J0`
+///
+/// A 96-bit IV is used as the nonce as is, with the counter starting at 1.
+/// Any other length gets hashed into a whole counter block first, whose last
+/// 4 bytes are where the counter starts.
+///
+/// # Panics
+///
+/// Will panic if `iv` is empty
+fn counter<'a, C: BlockCipher<Block = [u8; BLOCK_SIZE]>>(
+    cipher: &'a C,
+    h: &[u8; BLOCK_SIZE],
+    iv: &[u8])
+    -> Ctr<'a, C>
+{
+    assert!(!iv.is_empty(), "error: a GCM IV can't be empty");
+
+    if iv.len() == 12 {
+        let mut ctr = Ctr::new(cipher, CounterLayout::Nonce96Counter32Be, iv);
+        ctr.seek(BLOCK_SIZE as u64);
+
+        return ctr;
+    }
+
+    let pre_counter_block = ghash(h, &[], iv);
+
+    let mut ctr = Ctr::new(cipher, CounterLayout::Nonce96Counter32Be, &pre_counter_block[..12]);
+
+    let mut count = [0u8; 4];
+    count.copy_from_slice(&pre_counter_block[12..]);
+    ctr.seek(u32::from_be_bytes(count) as u64 * BLOCK_SIZE as u64);
+
+    ctr
+}
+
+/// The tag: GHASH, encrypted with the pre-counter block. `ctr` is left where
+/// it was, at the pre-counter block
+fn tag<C: BlockCipher<Block = [u8; BLOCK_SIZE]>>(
+    ctr: &mut Ctr<C>,
+    h: &[u8; BLOCK_SIZE],
+    aad: &[u8],
+    cipher_text: &[u8])
+    -> [u8; TAG_SIZE]
+{
+    let start = ctr.position();
+
+    let mut tag = ghash(h, aad, cipher_text);
+    ctr.apply(&mut tag);
+    ctr.seek(start);
+
+    tag
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use std::convert::TryFrom;
+
+    use crate::aes128::{gcm, Key, Nonce};
+    use crate::radix;
+
+    fn hex(input: &str) -> Vec<u8> {
+        radix::base16_to_bytes(input)
+    }
+
+    fn block(input: &str) -> [u8; 16] {
+        <[u8; 16]>::try_from(&hex(input)[..]).unwrap()
+    }
+
+    #[test]
+    fn test_gf128_mul() {
+        // x^0 (the first bit) is 1
+        let one = block("80000000000000000000000000000000");
+        let y = block("66e94bd4ef8a2c3b884cfa59ca342b2e");
+
+        assert_eq!(y, gcm::gf128_mul(&one, &y));
+        assert_eq!(y, gcm::gf128_mul(&y, &one));
+        assert_eq!([0; 16], gcm::gf128_mul(&[0; 16], &y));
+
+        // x^127 * x = x^128, which is x^7 + x^2 + x + 1
+        let x = block("40000000000000000000000000000000");
+        let x_127 = block("00000000000000000000000000000001");
+        assert_eq!(block("e1000000000000000000000000000000"), gcm::gf128_mul(&x_127, &x));
+    }
+
+    #[test]
+    fn test_ghash() {
+        // GCM spec test case 2: H and GHASH(H, {}, C)
+        let h = block("66e94bd4ef8a2c3b884cfa59ca342b2e");
+
+        assert_eq!(
+            block("f38cbb1ad69223dcc3457ae5b6b0f885"),
+            gcm::ghash(&h, &[], &hex("0388dace60b6a392f328c2b971b2fe78"))
+        );
+    }
+
+    #[test]
+    fn test_nist_vectors() {
+        let key = block("feffe9928665731c6d6a8f9467308308");
+        let plain_text = hex(concat!(
+            "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a72",
+            "1c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39",
+        ));
+        let aad = hex("feedfacedeadbeeffeedfacedeadbeefabaddad2");
+
+        // (key, iv, aad, plain text, cipher text, tag) from the GCM spec's
+        // test cases 1 to 6: an empty message, no associated data, and 64-,
+        // 96- and 480-bit IVs
+        let vectors = [
+            (
+                [0; 16],
+                hex("000000000000000000000000"),
+                vec![],
+                vec![],
+                "",
+                "58e2fccefa7e3061367f1d57a4e7455a",
+            ),
+            (
+                [0; 16],
+                hex("000000000000000000000000"),
+                vec![],
+                vec![0; 16],
+                "0388dace60b6a392f328c2b971b2fe78",
+                "ab6e47d42cec13bdf53a67b21257bddf",
+            ),
+            (
+                key,
+                hex("cafebabefacedbaddecaf888"),
+                vec![],
+                hex(concat!(
+                    "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a72",
+                    "1c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b391aafd255",
+                )),
+                concat!(
+                    "42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e",
+                    "21d514b25466931c7d8f6a5aac84aa051ba30b396a0aac973d58e091473f5985",
+                ),
+                "4d5c2af327cd64a62cf35abd2ba6fab4",
+            ),
+            (
+                key,
+                hex("cafebabefacedbaddecaf888"),
+                aad.clone(),
+                plain_text.clone(),
+                concat!(
+                    "42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e",
+                    "21d514b25466931c7d8f6a5aac84aa051ba30b396a0aac973d58e091",
+                ),
+                "5bc94fbc3221a5db94fae95ae7121a47",
+            ),
+            (
+                key,
+                hex("cafebabefacedbad"),
+                aad.clone(),
+                plain_text.clone(),
+                concat!(
+                    "61353b4c2806934a777ff51fa22a4755699b2a714fcdc6f83766e5f97b6c7423",
+                    "73806900e49f24b22b097544d4896b424989b5e1ebac0f07c23f4598",
+                ),
+                "3612d2e79e3b0785561be14aaca2fccb",
+            ),
+            (
+                key,
+                hex(concat!(
+                    "9313225df88406e555909c5aff5269aa6a7a9538534f7da1e4c303d2a318a728",
+                    "c3c0c95156809539fcf0e2429a6b525416aedbf5a0de6a57a637b39b",
+                )),
+                aad,
+                plain_text,
+                concat!(
+                    "8ce24998625615b603a033aca13fb894be9112a5c3a211a8ba262a3cca7e2ca7",
+                    "01e4a9a4fba43c90ccdcb281d48c7c6fd62875d2aca417034c34aee5",
+                ),
+                "619cc5aefffe0bfa462af43c1699d050",
+            ),
+        ];
+
+        for (key, iv, aad, plain_text, cipher_text, tag) in vectors.iter() {
+            let key = &Key::from_bytes(*key);
+            let iv = &Nonce::from_bytes(iv);
+
+            let (actual_cipher_text, actual_tag) = gcm::gcm_encrypt(key, iv, aad, plain_text);
+
+            assert_eq!(*cipher_text, radix::bytes_to_base16(&actual_cipher_text));
+            assert_eq!(*tag, radix::bytes_to_base16(&actual_tag));
+
+            assert_eq!(
+                Ok(plain_text.clone()),
+                gcm::gcm_decrypt(key, iv, aad, &actual_cipher_text, &actual_tag)
+            );
+        }
+    }
+
+    #[test]
+    fn test_fails_closed() {
+        let key = Key::from_bytes(*b"YELLOW SUBMARINE");
+        let iv = Nonce::from_bytes(&[7; 12]);
+
+        let (mut cipher_text, mut tag) = gcm::gcm_encrypt(&key, &iv, b"header", b"attack at dawn");
+
+        // Flip a bit in the cipher text, the associated data, the tag and
+        // the IV in turn
+        cipher_text[0] ^= 1;
+        assert_eq!(Err(gcm::GcmError::TagMismatch), gcm::gcm_decrypt(&key, &iv, b"header", &cipher_text, &tag));
+        cipher_text[0] ^= 1;
+
+        assert_eq!(Err(gcm::GcmError::TagMismatch), gcm::gcm_decrypt(&key, &iv, b"headed", &cipher_text, &tag));
+
+        tag[15] ^= 0x80;
+        assert_eq!(Err(gcm::GcmError::TagMismatch), gcm::gcm_decrypt(&key, &iv, b"header", &cipher_text, &tag));
+        tag[15] ^= 0x80;
+
+        assert_eq!(Err(gcm::GcmError::TagMismatch), gcm::gcm_decrypt(&key, &Nonce::from_bytes(&[7; 13]), b"header", &cipher_text, &tag));
+
+        assert_eq!(
+            Ok(b"attack at dawn".to_vec()),
+            gcm::gcm_decrypt(&key, &iv, b"header", &cipher_text, &tag)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "openssl")]
+    fn test_matches_openssl() {
+        use openssl::symm;
+
+        let key = *b"YELLOW SUBMARINE";
+
+        for (iv_len, len) in [(12, 0), (12, 31), (1, 16), (8, 17), (60, 100)].iter() {
+            let iv = (0..*iv_len).map(|i| i as u8 * 3).collect::<Vec<u8>>();
+            let msg = (0..*len).map(|i| (i * 13 + 5) as u8).collect::<Vec<u8>>();
+
+            let mut expected_tag = [0u8; 16];
+            let expected = symm::encrypt_aead(
+                symm::Cipher::aes_128_gcm(), &key, Some(&iv), b"aad", &msg, &mut expected_tag
+            ).unwrap();
+
+            assert_eq!(
+                (expected, expected_tag),
+                gcm::gcm_encrypt(&Key::from_bytes(key), &Nonce::from_bytes(&iv), b"aad", &msg)
+            );
+        }
+    }
+}
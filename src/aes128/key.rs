@@ -0,0 +1,385 @@
+//! # Keys, IVs and nonces
+//!
+//! A key, an IV and a nonce are all just a few bytes, and for a long time
+//! that's all they were here: `[u8; 16]`s that got copied around, printed in
+//! full by `{:?}` and left behind in memory once dropped. Worse, swapping a
+//! key and an IV in `cbc_encrypt(key, iv, msg)` went unnoticed.
+//!
+//! `Key`, `Iv` and `Nonce` fix that. They are distinct types, so mixing them
+//! up doesn't compile. Their `Debug` output doesn't show the bytes, and the
+//! bytes are zeroed when they are dropped. Making one is explicit: `random`
+//! (or `random_with` a `random::RandomSource`), `from_bytes`, `from_hex` or
+//! `from_base64`.
+//!
+//! ```
+//! use ez_cryptopals::aes128;
+//! use ez_cryptopals::aes128::{Iv, Key};
+//!
+//! let key = Key::from_bytes(*b"YELLOW SUBMARINE");
+//! let iv = Iv::from_hex("000102030405060708090a0b0c0d0e0f");
+//!
+//! let cipher_text = aes128::cbc_encrypt(&key, &iv, b"ICE ICE BABY");
+//!
+//! assert_eq!(b"ICE ICE BABY".to_vec(), aes128::cbc_decrypt(&key, &iv, &cipher_text).unwrap());
+//! assert_eq!("Key<128>(<redacted>)", format!("{:?}", key));
+//! ```
+//!
+//! Only the AES-specific functions take these. The `_with` versions and
+//! `BlockCipher::new` are the layer underneath, and still take plain arrays.
+
+use std::error;
+use std::fmt;
+use std::sync::atomic;
+
+use crate::aes128::{BLOCK_SIZE, Bits128, KeySize, mac};
+use crate::radix;
+use crate::radix::DecodeError;
+use crate::random::{OsRandom, RandomSource};
+
+/// A secret AES key, 128-bit unless `K` says otherwise
+pub struct Key<K: KeySize = Bits128> {
+    bytes: K::Key,
+}
+
+impl<K: KeySize> Key<K> {
+    /// A fresh random key
+    pub fn random() -> Self {
+        Key::random_with(&mut OsRandom)
+    }
+
+    /// A key from any source of random bytes
+    pub fn random_with<R: RandomSource + ?Sized>(random: &mut R) -> Self {
+        // Filled in place, so the bytes are never anywhere that won't be
+        // zeroed
+        let mut key = Key::from_bytes(K::Key::default());
+
+        random.fill_bytes(AsMut::<[u8]>::as_mut(&mut key.bytes));
+
+        key
+    }
+
+    pub fn from_bytes(bytes: K::Key) -> Self {
+        Key { bytes }
+    }
+
+    /// Panicking version of `try_from_hex`
+    pub fn from_hex(hex: &str) -> Self {
+        Key::try_from_hex(hex).unwrap_or_else(|e| panic!("error: {}", e))
+    }
+
+    /// Decode a key from hex. It has to be exactly the right length
+    pub fn try_from_hex(hex: &str) -> Result<Self, KeyError> {
+        let mut key = Key::from_bytes(K::Key::default());
+
+        decode_into(radix::try_base16_to_bytes(hex), AsMut::<[u8]>::as_mut(&mut key.bytes))?;
+
+        Ok(key)
+    }
+
+    /// Panicking version of `try_from_base64`
+    pub fn from_base64(base64: &str) -> Self {
+        Key::try_from_base64(base64).unwrap_or_else(|e| panic!("error: {}", e))
+    }
+
+    /// Decode a key from base64. It has to be exactly the right length
+    pub fn try_from_base64(base64: &str) -> Result<Self, KeyError> {
+        let mut key = Key::from_bytes(K::Key::default());
+
+        decode_into(radix::try_base64_to_bytes(base64), AsMut::<[u8]>::as_mut(&mut key.bytes))?;
+
+        Ok(key)
+    }
+
+    pub fn as_bytes(&self) -> &K::Key {
+        &self.bytes
+    }
+}
+
+impl<K: KeySize> Clone for Key<K> {
+    fn clone(&self) -> Self {
+        Key::from_bytes(self.bytes)
+    }
+}
+
+/// In constant time, so that comparing keys can't leak them
+impl<K: KeySize> PartialEq for Key<K> {
+    fn eq(&self, other: &Self) -> bool {
+        mac::constant_time_eq(self.bytes.as_ref(), other.bytes.as_ref())
+    }
+}
+
+impl<K: KeySize> Eq for Key<K> {}
+
+impl<K: KeySize> fmt::Debug for Key<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Key<{}>(<redacted>)", self.bytes.as_ref().len() * 8)
+    }
+}
+
+impl<K: KeySize> Drop for Key<K> {
+    fn drop(&mut self) {
+        zeroize(self.bytes.as_mut());
+    }
+}
+
+/// An initialization vector for CBC, CFB or OFB: one block
+pub struct Iv {
+    bytes: [u8; BLOCK_SIZE],
+}
+
+impl Iv {
+    /// A fresh random IV
+    pub fn random() -> Self {
+        Iv::random_with(&mut OsRandom)
+    }
+
+    /// An IV from any source of random bytes
+    pub fn random_with<R: RandomSource + ?Sized>(random: &mut R) -> Self {
+        let mut iv = Iv::from_bytes([0; BLOCK_SIZE]);
+
+        random.fill_bytes(&mut iv.bytes);
+
+        iv
+    }
+
+    pub fn from_bytes(bytes: [u8; BLOCK_SIZE]) -> Self {
+        Iv { bytes }
+    }
+
+    /// Panicking version of `try_from_hex`
+    pub fn from_hex(hex: &str) -> Self {
+        Iv::try_from_hex(hex).unwrap_or_else(|e| panic!("error: {}", e))
+    }
+
+    /// Decode an IV from hex. It has to be exactly a block long
+    pub fn try_from_hex(hex: &str) -> Result<Self, KeyError> {
+        let mut iv = Iv::from_bytes([0; BLOCK_SIZE]);
+
+        decode_into(radix::try_base16_to_bytes(hex), &mut iv.bytes)?;
+
+        Ok(iv)
+    }
+
+    /// Panicking version of `try_from_base64`
+    pub fn from_base64(base64: &str) -> Self {
+        Iv::try_from_base64(base64).unwrap_or_else(|e| panic!("error: {}", e))
+    }
+
+    /// Decode an IV from base64. It has to be exactly a block long
+    pub fn try_from_base64(base64: &str) -> Result<Self, KeyError> {
+        let mut iv = Iv::from_bytes([0; BLOCK_SIZE]);
+
+        decode_into(radix::try_base64_to_bytes(base64), &mut iv.bytes)?;
+
+        Ok(iv)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; BLOCK_SIZE] {
+        &self.bytes
+    }
+}
+
+impl Clone for Iv {
+    fn clone(&self) -> Self {
+        Iv::from_bytes(self.bytes)
+    }
+}
+
+impl fmt::Debug for Iv {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Iv(<redacted>)")
+    }
+}
+
+impl Drop for Iv {
+    fn drop(&mut self) {
+        zeroize(&mut self.bytes);
+    }
+}
+
+/// A nonce, e.g. the IV of GCM. Unlike an `Iv`, it can be any length
+pub struct Nonce {
+    bytes: Vec<u8>,
+}
+
+impl Nonce {
+    /// A fresh random nonce, `len` bytes long
+    pub fn random(len: usize) -> Self {
+        Nonce::random_with(len, &mut OsRandom)
+    }
+
+    /// A nonce `len` bytes long, from any source of random bytes
+    pub fn random_with<R: RandomSource + ?Sized>(len: usize, random: &mut R) -> Self {
+        let mut nonce = Nonce::from_bytes(&vec![0; len]);
+
+        random.fill_bytes(&mut nonce.bytes);
+
+        nonce
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        Nonce { bytes: bytes.to_vec() }
+    }
+
+    /// The 8-byte nonce of cryptopals' CTR mode, which gives it as a number:
+    /// `nonce` as a 64-bit little endian number
+    pub fn from_u64(nonce: u64) -> Self {
+        Nonce::from_bytes(&nonce.to_le_bytes())
+    }
+
+    /// Panicking version of `try_from_hex`
+    pub fn from_hex(hex: &str) -> Self {
+        Nonce::try_from_hex(hex).unwrap_or_else(|e| panic!("error: {}", e))
+    }
+
+    /// Decode a nonce from hex
+    pub fn try_from_hex(hex: &str) -> Result<Self, KeyError> {
+        let bytes = radix::try_base16_to_bytes(hex).map_err(KeyError::Decode)?;
+
+        Ok(Nonce { bytes })
+    }
+
+    /// Panicking version of `try_from_base64`
+    pub fn from_base64(base64: &str) -> Self {
+        Nonce::try_from_base64(base64).unwrap_or_else(|e| panic!("error: {}", e))
+    }
+
+    /// Decode a nonce from base64
+    pub fn try_from_base64(base64: &str) -> Result<Self, KeyError> {
+        let bytes = radix::try_base64_to_bytes(base64).map_err(KeyError::Decode)?;
+
+        Ok(Nonce { bytes })
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl Clone for Nonce {
+    fn clone(&self) -> Self {
+        Nonce::from_bytes(&self.bytes)
+    }
+}
+
+impl fmt::Debug for Nonce {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Nonce<{}>(<redacted>)", self.bytes.len() * 8)
+    }
+}
+
+impl Drop for Nonce {
+    fn drop(&mut self) {
+        zeroize(&mut self.bytes);
+    }
+}
+
+/// Why a key, IV or nonce couldn't be decoded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyError {
+    /// The hex or base64 itself is bad
+    Decode(DecodeError),
+
+    /// It decoded fine, but to the wrong number of bytes
+    WrongLength { expected: usize, actual: usize },
+}
+
+impl fmt::Display for KeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            KeyError::Decode(e) => write!(f, "{}", e),
+            KeyError::WrongLength { expected, actual } => {
+                write!(f, "expected {} bytes, got {}", expected, actual)
+            },
+        }
+    }
+}
+
+impl error::Error for KeyError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            KeyError::Decode(e) => Some(e),
+            KeyError::WrongLength { .. } => None,
+        }
+    }
+}
+
+/// Copy what was decoded into `out`, if it's the right length. Either way,
+/// the decoded copy is zeroed
+fn decode_into(decoded: Result<Vec<u8>, DecodeError>, out: &mut [u8]) -> Result<(), KeyError> {
+    let mut decoded = decoded.map_err(KeyError::Decode)?;
+
+    let result = if decoded.len() == out.len() {
+        out.copy_from_slice(&decoded);
+
+        Ok(())
+    }
+    else {
+        Err(KeyError::WrongLength { expected: out.len(), actual: decoded.len() })
+    };
+
+    zeroize(&mut decoded);
+
+    result
+}
+
+/// Overwrite `bytes` with zeros, in a way the compiler won't optimize out
+/// for being a write to something that's about to be dropped anyway
+pub fn zeroize(bytes: &mut [u8]) {
+    for byte in bytes.iter_mut() {
+        // Safe: `byte` is a valid, aligned `&mut u8`
+        unsafe { std::ptr::write_volatile(byte, 0) };
+    }
+
+    atomic::compiler_fence(atomic::Ordering::SeqCst);
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use crate::aes128::{Bits128, Bits256, Iv, Key, KeyError, Nonce};
+    use crate::aes128::key;
+    use crate::radix::DecodeError;
+
+    #[test]
+    fn test_constructors() {
+        let key: Key = Key::from_bytes(*b"YELLOW SUBMARINE");
+
+        assert_eq!(key, Key::from_hex("59454c4c4f57205355424d4152494e45"));
+        assert_eq!(key, Key::from_base64("WUVMTE9XIFNVQk1BUklORQ=="));
+        assert_ne!(key, Key::random());
+
+        assert_eq!(&[7; 32], Key::<Bits256>::from_hex(&"07".repeat(32)).as_bytes());
+
+        assert_eq!(&[0xff; 16], Iv::from_base64("/////////////////////w==").as_bytes());
+        assert_eq!(&[1, 2, 3], Nonce::from_hex("010203").as_bytes());
+        assert_eq!(12, Nonce::random(12).as_bytes().len());
+        assert_eq!(&[1, 0, 0, 0, 0, 0, 0, 0], Nonce::from_u64(1).as_bytes());
+
+        assert_eq!(
+            Err(KeyError::WrongLength { expected: 16, actual: 15 }),
+            Key::<Bits128>::try_from_hex(&"00".repeat(15))
+        );
+        assert_eq!(
+            KeyError::Decode(DecodeError::InvalidCharacter { byte: b'x', offset: 1 }),
+            Iv::try_from_hex("0x").unwrap_err()
+        );
+    }
+
+    #[test]
+    fn test_debug_is_redacted() {
+        let key = Key::<Bits256>::from_bytes([0x41; 32]);
+
+        assert_eq!("Key<256>(<redacted>)", format!("{:?}", key));
+        assert_eq!("Iv(<redacted>)", format!("{:?}", Iv::from_bytes([0x41; 16])));
+        assert_eq!("Nonce<96>(<redacted>)", format!("{:?}", Nonce::from_bytes(&[0x41; 12])));
+    }
+
+    #[test]
+    fn test_zeroize() {
+        let mut bytes = *b"YELLOW SUBMARINE";
+
+        key::zeroize(&mut bytes);
+
+        assert_eq!([0; 16], bytes);
+    }
+}
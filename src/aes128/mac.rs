@@ -0,0 +1,401 @@
+//! # CBC-MAC and CMAC
+//!
+//! Encrypt a message in CBC mode and throw away everything except the last
+//! block: that's a CBC-MAC. Every block of the message went into it, so
+//! changing any of them changes the last block, and without the key it
+//! can't be computed.
+//!
+//! Except that's only true for messages of one fixed length. Given the MAC
+//! `t` of a (padded) message `m`, the MAC of `m || (m' ^ t)` is the MAC of
+//! `m'`, where only the first block of `m'` is XORed with `t`. That block
+//! goes into the cipher exactly as it did when `m'` was MACed on its own
+//! (with a zero IV). Length extension for free.
+//!
+//! CMAC (RFC 4493, NIST SP 800-38B) fixes that. Before the last block goes
+//! in, it gets XORed with one of two subkeys derived from the key: `K1` if
+//! it was a whole block, `K2` if it had to be padded. Without knowing those,
+//! a MAC can't be continued.
+//!
+//! Both can be fed a message in pieces with `update` and then `finalize`d,
+//! or `verify`d against a MAC in constant time.
+
+use crate::aes128::{Aes128, BLOCK_SIZE, Key};
+use crate::block_cipher::BlockCipher;
+
+/// CBC-MAC a message with AES-128 and a zero IV
+///
+/// The message is PKCS#7 padded, so this is the last block of `cbc_encrypt`
+/// with a zero IV.
+pub fn cbc_mac(key: &Key, msg: &[u8]) -> [u8; BLOCK_SIZE] {
+    let cipher = Aes128::with_key(key);
+
+    let mut mac = CbcMac::new(&cipher);
+    mac.update(msg);
+
+    mac.finalize()
+}
+
+/// CMAC a message with AES-128 (RFC 4493)
+pub fn cmac(key: &Key, msg: &[u8]) -> [u8; BLOCK_SIZE] {
+    let cipher = Aes128::with_key(key);
+
+    let mut mac = Cmac::new(&cipher);
+    mac.update(msg);
+
+    mac.finalize()
+}
+
+/// Check a message's AES-128 CMAC, in constant time
+pub fn cmac_verify(key: &Key, msg: &[u8], tag: &[u8]) -> bool {
+    let cipher = Aes128::with_key(key);
+
+    let mut mac = Cmac::new(&cipher);
+    mac.update(msg);
+
+    mac.verify(tag)
+}
+
+/// Compare two MACs without returning early on the first difference, so
+/// the time it takes doesn't say how much of a forged MAC was right. The
+/// lengths aren't secret, so different lengths do return right away
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    a.iter().zip(b.iter()).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// The CBC part that both MACs share: a running state that every whole
+/// block gets XORed into and encrypted, plus a partial block waiting for
+/// more input
+struct Chain<'a, C: BlockCipher> {
+    cipher: &'a C,
+    state: C::Block,
+    buffer: C::Block,
+    buffered: usize,
+}
+
+impl<'a, C: BlockCipher> Chain<'a, C> {
+    fn new(cipher: &'a C, iv: &C::Block) -> Self {
+        Chain {
+            cipher,
+            state: *iv,
+            buffer: C::Block::default(),
+            buffered: 0,
+        }
+    }
+
+    /// Add `data` to the buffer, chaining in every block that fills up. If
+    /// `hold_last` is set, a full buffer is only chained in once more data
+    /// comes after it, since CMAC treats the last block differently
+    fn update(&mut self, data: &[u8], hold_last: bool) {
+        let mut data = data;
+
+        while !data.is_empty() {
+            if self.buffered == C::BLOCK_SIZE {
+                self.chain_buffer();
+            }
+
+            let count = (C::BLOCK_SIZE - self.buffered).min(data.len());
+
+            self.buffer.as_mut()[self.buffered..self.buffered + count].copy_from_slice(&data[..count]);
+            self.buffered += count;
+            data = &data[count..];
+
+            if self.buffered == C::BLOCK_SIZE && !hold_last {
+                self.chain_buffer();
+            }
+        }
+    }
+
+    /// XOR the (full) buffer into the state and encrypt it
+    fn chain_buffer(&mut self) {
+        for (state_byte, byte) in self.state.as_mut().iter_mut().zip(self.buffer.as_ref()) {
+            *state_byte ^= byte;
+        }
+
+        self.cipher.encrypt_block(&mut self.state);
+        self.buffered = 0;
+    }
+}
+
+/// A CBC-MAC in progress, for any block cipher
+///
+/// The message is PKCS#7 padded when it is finalized, like `cbc_encrypt`
+/// would.
+pub struct CbcMac<'a, C: BlockCipher> {
+    chain: Chain<'a, C>,
+}
+
+impl<'a, C: BlockCipher> CbcMac<'a, C> {
+    /// Start a CBC-MAC with a zero IV
+    pub fn new(cipher: &'a C) -> Self {
+        Self::with_iv(cipher, &C::Block::default())
+    }
+
+    /// Start a CBC-MAC with the given IV. Letting whoever sends the message
+    /// pick it is one of the classic mistakes
+    pub fn with_iv(cipher: &'a C, init_vector: &C::Block) -> Self {
+        CbcMac { chain: Chain::new(cipher, init_vector) }
+    }
+
+    /// Add more of the message
+    pub fn update(&mut self, data: &[u8]) {
+        self.chain.update(data, false);
+    }
+
+    /// Pad the message and return the MAC
+    pub fn finalize(mut self) -> C::Block {
+        // Always at least one byte of padding, since nothing is ever left
+        // in a full buffer
+        let count = C::BLOCK_SIZE - self.chain.buffered;
+
+        self.chain.update(&vec![count as u8; count], false);
+
+        self.chain.state
+    }
+
+    /// Finalize, and check the MAC against `tag` in constant time
+    pub fn verify(self, tag: &[u8]) -> bool {
+        constant_time_eq(self.finalize().as_ref(), tag)
+    }
+}
+
+/// A CMAC in progress, for any block cipher with 8- or 16-byte blocks
+pub struct Cmac<'a, C: BlockCipher> {
+    chain: Chain<'a, C>,
+    k1: C::Block,
+    k2: C::Block,
+}
+
+impl<'a, C: BlockCipher> Cmac<'a, C> {
+    /// # Panics
+    ///
+    /// Will panic if `C`'s blocks aren't 8 or 16 bytes, see `cmac_subkeys`
+    pub fn new(cipher: &'a C) -> Self {
+        let (k1, k2) = cmac_subkeys(cipher);
+
+        Cmac {
+            chain: Chain::new(cipher, &C::Block::default()),
+            k1,
+            k2,
+        }
+    }
+
+    /// Add more of the message
+    pub fn update(&mut self, data: &[u8]) {
+        self.chain.update(data, true);
+    }
+
+    /// Mix the right subkey into the last block, chain it in and return the
+    /// MAC
+    pub fn finalize(mut self) -> C::Block {
+        let subkey = if self.chain.buffered == C::BLOCK_SIZE {
+            self.k1
+        }
+        else {
+            // 10* padding: a single 1 bit, then zeros
+            let buffered = self.chain.buffered;
+            let buffer = self.chain.buffer.as_mut();
+
+            buffer[buffered] = 0x80;
+
+            for byte in buffer[buffered + 1..].iter_mut() {
+                *byte = 0;
+            }
+
+            self.k2
+        };
+
+        for (byte, subkey_byte) in self.chain.buffer.as_mut().iter_mut().zip(subkey.as_ref()) {
+            *byte ^= subkey_byte;
+        }
+
+        self.chain.chain_buffer();
+
+        self.chain.state
+    }
+
+    /// Finalize, and check the MAC against `tag` in constant time
+    pub fn verify(self, tag: &[u8]) -> bool {
+        constant_time_eq(self.finalize().as_ref(), tag)
+    }
+}
+
+/// Derive CMAC's two subkeys, `K1` and `K2` (RFC 4493 §2.3)
+///
+/// `L` is the encryption of a block of zeros. `K1` is `L` doubled in
+/// GF(2^128), i.e. shifted left by a bit with the top bit folded back in
+/// as a constant. `K2` is `K1` doubled.
+///
+/// # Panics
+///
+/// Will panic if `C`'s blocks aren't 8 or 16 bytes. Those are the only
+/// sizes with a constant defined
+pub fn cmac_subkeys<C: BlockCipher>(cipher: &C) -> (C::Block, C::Block) {
+    let constant = match C::BLOCK_SIZE {
+        16 => 0x87,
+        8 => 0x1b,
+        size => panic!("error: CMAC needs 8 or 16-byte blocks, got {}", size),
+    };
+
+    let mut l = C::Block::default();
+    cipher.encrypt_block(&mut l);
+
+    let k1 = double(&l, constant);
+    let k2 = double(&k1, constant);
+
+    (k1, k2)
+}
+
+/// Shift a block left by one bit, and XOR `constant` into the last byte if
+/// a bit fell off the front
+fn double<B: Copy + AsRef<[u8]> + AsMut<[u8]>>(block: &B, constant: u8) -> B {
+    let mut doubled = *block;
+    let bytes = doubled.as_mut();
+
+    let carry = bytes[0] >> 7;
+
+    for i in 0..bytes.len() {
+        let next = bytes.get(i + 1).map_or(0, |byte| byte >> 7);
+        bytes[i] = bytes[i] << 1 | next;
+    }
+
+    // `0 - carry` is all ones if the top bit was set, without branching
+    *bytes.last_mut().unwrap() ^= constant & 0u8.wrapping_sub(carry);
+
+    doubled
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use std::convert::TryFrom;
+
+    use crate::aes128;
+    use crate::aes128::{Aes128, Iv, Key};
+    use crate::aes128::mac::{CbcMac, Cmac};
+    use crate::block_cipher::BlockCipher;
+    use crate::radix;
+
+    fn block(input: &str) -> [u8; 16] {
+        <[u8; 16]>::try_from(&radix::base16_to_bytes(input)[..]).unwrap()
+    }
+
+    fn rfc_4493_message() -> Vec<u8> {
+        radix::base16_to_bytes(concat!(
+            "6bc1bee22e409f96e93d7e117393172a",
+            "ae2d8a571e03ac9c9eb76fac45af8e51",
+            "30c81c46a35ce411e5fbc1191a0a52ef",
+            "f69f2445df4f9b17ad2b417be66c3710",
+        ))
+    }
+
+    #[test]
+    fn test_cmac_subkeys() {
+        // RFC 4493 §4, Subkey Generation
+        let cipher = Aes128::new(&block("2b7e151628aed2a6abf7158809cf4f3c"));
+
+        let (k1, k2) = aes128::mac::cmac_subkeys(&cipher);
+
+        assert_eq!(block("fbeed618357133667c85e08f7236a8de"), k1);
+        assert_eq!(block("f7ddac306ae266ccf90bc11ee46d513b"), k2);
+    }
+
+    #[test]
+    fn test_cmac_vectors() {
+        let key = Key::from_hex("2b7e151628aed2a6abf7158809cf4f3c");
+        let msg = rfc_4493_message();
+
+        // RFC 4493 §4, Examples 1 to 4
+        let vectors = [
+            (0, "bb1d6929e95937287fa37d129b756746"),
+            (16, "070a16b46b4d4144f79bdd9dd04a287c"),
+            (40, "dfa66747de9ae63030ca32611497c827"),
+            (64, "51f0bebf7e3b9d92fc49741779363cfe"),
+        ];
+
+        for (len, expected) in vectors.iter() {
+            assert_eq!(block(expected), aes128::cmac(&key, &msg[..*len]));
+            assert!(aes128::cmac_verify(&key, &msg[..*len], &block(expected)));
+        }
+    }
+
+    #[test]
+    fn test_streaming() {
+        let key = Key::from_hex("2b7e151628aed2a6abf7158809cf4f3c");
+        let cipher = Aes128::with_key(&key);
+        let msg = rfc_4493_message();
+
+        // Split everywhere, including right on block boundaries
+        for split in 0..=msg.len() {
+            let mut cmac = Cmac::new(&cipher);
+            cmac.update(&msg[..split]);
+            cmac.update(&[]);
+            cmac.update(&msg[split..]);
+
+            assert_eq!(block("51f0bebf7e3b9d92fc49741779363cfe"), cmac.finalize());
+
+            let mut cbc_mac = CbcMac::new(&cipher);
+            cbc_mac.update(&msg[..split]);
+            cbc_mac.update(&msg[split..]);
+
+            assert_eq!(aes128::cbc_mac(&key, &msg), cbc_mac.finalize());
+        }
+    }
+
+    #[test]
+    fn test_cbc_mac_is_cbc() {
+        let key = Key::from_bytes(*b"YELLOW SUBMARINE");
+
+        for len in [0, 1, 16, 33].iter() {
+            let msg = vec![b'A'; *len];
+            let cipher_text = aes128::cbc_encrypt(&key, &Iv::from_bytes([0; 16]), &msg);
+
+            assert_eq!(cipher_text[cipher_text.len() - 16..], aes128::cbc_mac(&key, &msg));
+        }
+    }
+
+    #[test]
+    fn test_cbc_mac_length_extension() {
+        let key = Key::from_bytes(*b"YELLOW SUBMARINE");
+
+        let msg = b"from=alice&to=bo";
+        let other_msg = b"b&amount=1000000 and some more";
+
+        let tag = aes128::cbc_mac(&key, msg);
+
+        // msg, its padding, then other_msg with its first block XORed with
+        // the tag
+        let mut forged = msg.to_vec();
+        forged.extend_from_slice(&[16; 16]);
+        forged.extend(other_msg[..16].iter().zip(tag.iter()).map(|(a, b)| a ^ b));
+        forged.extend_from_slice(&other_msg[16..]);
+
+        assert_eq!(aes128::cbc_mac(&key, other_msg), aes128::cbc_mac(&key, &forged));
+
+        // CMAC isn't fooled
+        let tag = aes128::cmac(&key, msg);
+
+        let mut forged = msg.to_vec();
+        forged.extend(other_msg[..16].iter().zip(tag.iter()).map(|(a, b)| a ^ b));
+        forged.extend_from_slice(&other_msg[16..]);
+
+        assert_ne!(aes128::cmac(&key, other_msg), aes128::cmac(&key, &forged));
+    }
+
+    #[test]
+    fn test_verify() {
+        let key = Key::from_bytes(*b"YELLOW SUBMARINE");
+        let cipher = Aes128::with_key(&key);
+        let tag = aes128::cmac(&key, b"from=alice&to=bob&amount=10");
+
+        assert!(aes128::cmac_verify(&key, b"from=alice&to=bob&amount=10", &tag));
+        assert!(!aes128::cmac_verify(&key, b"from=alice&to=eve&amount=10", &tag));
+        assert!(!aes128::cmac_verify(&key, b"from=alice&to=bob&amount=10", &tag[..15]));
+
+        let mut cbc_mac = CbcMac::new(&cipher);
+        cbc_mac.update(b"from=alice");
+        assert!(cbc_mac.verify(&aes128::cbc_mac(&key, b"from=alice")));
+    }
+}
@@ -0,0 +1,2070 @@
+//! # Implementations of a few AES cipher modes
+//!
+//! From what I am understanding, block ciphers are only designed to provide
+//! confidentiality for the block they encrypt.    
+//! If I need to encrypt more than a block, which is often the case, I need to
+//! layer an algorithm on top of the block cipher that determines how to 
+//! construct the subsequent blocks in a hopefully still secure manner.  
+//! This _layered_ algorithm is called the cipher's _mode of operation_
+//! 
+//! ## ECB
+//! 
+//! ECB is one such mode of operation. It stands for Electronic Code Book.
+//! Calling it a "mode of operation" is actually not helpful. ECB just uses the 
+//! AES algorithm to decrypt/encrypt for every block that is fed to it. Nothing 
+//! fancy is done.
+//! 
+//! ## CBC
+//! 
+//! CBC is another mode of operation. It stands for Cipher Block Chaining.
+//! Contrary to ECB, CBC actually does stuff to the input apart from feeding it
+//! through the block cipher. More specifically, it XORs the previous block 
+//! of cipher text with the current block of plain text, hence the "Chain" part
+//! of its name
+//!
+//! ## Padding
+//!
+//! ECB and CBC only work on whole blocks, so messages get padded. The plain
+//! functions use PKCS#7. The `_with` versions take a `Padding`, which has
+//! the other common schemes, and `Padding::None` for raw block streams.
+//!
+//! ## In place
+//!
+//! Every ECB and CBC function above hands back a fresh `Vec`. The
+//! `_in_place` versions (`cbc_decrypt_in_place` etc.) work on a `&mut [u8]`
+//! instead and allocate nothing, which is what a padding oracle attack
+//! doing millions of decryptions wants. Encrypting, the buffer needs room
+//! after the message for the padding.
+//!
+//! ## CFB and OFB
+//!
+//! Two older ways of making a stream cipher out of a block cipher, which
+//! still turn up in legacy data. In both, the message is XORed with the
+//! output of the block cipher instead of being fed through it, so nothing
+//! needs padding.
+//!
+//! CFB (Cipher Feedback) encrypts the previous block of cipher text to get
+//! the keystream for the next one. CFB-8 does the same a byte at a time:
+//! encrypt the last 16 bytes of cipher text, use the first byte of the
+//! result, shift in the new cipher text byte, repeat. Sixteen times the work
+//! of CFB-128.
+//!
+//! OFB (Output Feedback) just keeps encrypting the IV, over and over. The
+//! keystream never depends on the message at all.
+//!
+//! ## CTR
+//!
+//! CTR (counter mode) turns the block cipher into a stream cipher. It
+//! encrypts a counter block (a nonce, followed by the number of the block)
+//! and XORs the result into the message. Encrypting and decrypting are the
+//! same thing, nothing needs padding, and since block `n`'s keystream only
+//! depends on `n`, it can start from any byte of the message.
+//! `ctr_apply_with` takes the counter layout and where to start, and `Ctr`
+//! does the same for a message that comes in pieces.
+//!
+//! ## GCM
+//!
+//! The only mode here that also authenticates: CTR, plus a tag computed with
+//! GHASH. It lives in `gcm`, along with GHASH itself.
+//!
+//! ## MACs
+//!
+//! `mac` has CBC-MAC, which is CBC with everything but the last block
+//! thrown away, and CMAC, which is CBC-MAC done right.
+//!
+//! ## Streaming
+//!
+//! For files too big to keep in memory, `stream` has ECB and CBC encryptors
+//! that wrap an `io::Write`, and decryptors that wrap an `io::Read`.
+//!
+//! ## Known answer tests
+//!
+//! `cavp` reads NIST's `.rsp` test vector files and runs every vector
+//! through the modes here. The files it's tested against are in
+//! `src/aes128/input/cavp`.
+//!
+//! ## Other block ciphers
+//!
+//! Neither mode actually cares that the block cipher is AES. The `_with`
+//! versions (`cbc_encrypt_with` etc.) take any `block_cipher::BlockCipher`.
+//! The plain versions are those, with `Aes128` and PKCS#7 padding.
+//!
+//! ## Key sizes
+//!
+//! Despite the module's name, AES-192 and AES-256 are here too. The key size
+//! is picked with a type: `Aes<Bits128>`, `Aes<Bits192>` or `Aes<Bits256>`
+//! (or `Aes128`, `Aes192` and `Aes256` for short), each of which takes a key
+//! array of the right length (or a `Key<Bits256>` etc. with `with_key`).
+//! Those go into the `_with` modes like any other block cipher, and
+//! `Key::<Bits256>::random()` makes a key to go with them.
+//!
+//! ## Keys, IVs and nonces
+//!
+//! The plain AES functions take a `Key`, an `Iv` or a `Nonce` rather than
+//! bare arrays, so that they can't be mixed up and don't get printed or left
+//! lying around in memory. See `key`.
+//!
+//! ## Backends
+//!
+//! By default, single blocks are encrypted by openssl. With the `soft-aes`
+//! feature, they go through the from-scratch implementation in `soft`
+//! instead. Building with `--no-default-features --features soft-aes` leaves
+//! openssl (and libssl) out altogether.
+
+
+
+#[cfg(feature = "openssl")]
+use openssl::symm;
+#[cfg(all(feature = "openssl", not(feature = "soft-aes")))]
+use std::cell::RefCell;
+use std::error;
+use std::fmt;
+use std::marker::PhantomData;
+
+use crate::block_cipher;
+use crate::block_cipher::BlockCipher;
+use crate::random::{OsRandom, RandomSource};
+
+pub mod cavp;
+pub mod gcm;
+pub mod key;
+pub mod mac;
+pub mod soft;
+pub mod stream;
+
+pub use self::gcm::{gcm_encrypt, gcm_decrypt, gcm_encrypt_with, gcm_decrypt_with, GcmError};
+pub use self::key::{Iv, Key, KeyError, Nonce};
+pub use self::mac::{cbc_mac, cmac, cmac_verify, CbcMac, Cmac};
+
+pub const BLOCK_SIZE: usize = 16;
+
+
+#[derive(Debug, PartialEq)]
+pub enum CipherMode {
+    ECB, CBC, CTR, CFB128, CFB8, OFB, GCM
+}
+
+//////////////////////
+/* CIPHER MODE: CBC */
+//////////////////////
+
+/// Encrypt a message with AES in Cipher Block Chaining mode
+pub fn cbc_encrypt(
+    key: &Key,
+    init_vector: &Iv,
+    msg: &[u8]) -> Vec<u8>
+{
+    cbc_encrypt_with(&Aes128::with_key(key), init_vector.as_bytes(), Padding::Pkcs7, msg, &mut OsRandom)
+}
+
+/// Encrypt a message with any block cipher and padding in Cipher Block
+/// Chaining mode
+///
+/// `random` is only used by `Padding::Iso10126`, for its filler bytes.
+///
+/// # Panics
+///
+/// Will panic with `Padding::None` if `msg` isn't a whole number of blocks
+pub fn cbc_encrypt_with<C: BlockCipher, R: RandomSource + ?Sized>(
+    cipher: &C,
+    init_vector: &C::Block,
+    padding: Padding,
+    msg: &[u8],
+    random: &mut R)
+    -> Vec<u8>
+{
+    // Room for the message and up to a block of padding
+    let mut buffer = msg.to_vec();
+    buffer.resize(msg.len() + C::BLOCK_SIZE, 0);
+
+    let length = cbc_encrypt_in_place_with(cipher, init_vector, padding, &mut buffer, msg.len(), random).len();
+    buffer.truncate(length);
+
+    buffer
+}
+
+/// Encrypt the first `msg_len` bytes of `buffer` with AES in Cipher Block
+/// Chaining mode, in place
+///
+/// This keys a new `Aes128` on every call, and with openssl that allocates.
+/// In a hot loop, key one with `Aes128::with_key` and call
+/// `cbc_encrypt_in_place_with` instead.
+pub fn cbc_encrypt_in_place<'a>(
+    key: &Key,
+    init_vector: &Iv,
+    buffer: &'a mut [u8],
+    msg_len: usize)
+    -> &'a mut [u8]
+{
+    cbc_encrypt_in_place_with(&Aes128::with_key(key), init_vector.as_bytes(), Padding::Pkcs7, buffer, msg_len, &mut OsRandom)
+}
+
+/// Encrypt the first `msg_len` bytes of `buffer` with any block cipher and
+/// padding in Cipher Block Chaining mode, in place
+///
+/// The padding goes in the rest of `buffer`, so it needs room for up to a
+/// block more than the message. Returns the cipher text, which is the front
+/// of `buffer`. Nothing is allocated along the way, apart from whatever the
+/// cipher does, so key the cipher once and reuse it in hot loops. `random`
+/// is only used by `Padding::Iso10126`.
+///
+/// # Panics
+///
+/// Will panic if the padding doesn't fit in `buffer`, or with
+/// `Padding::None` if `msg_len` isn't a whole number of blocks
+pub fn cbc_encrypt_in_place_with<'a, C: BlockCipher, R: RandomSource + ?Sized>(
+    cipher: &C,
+    init_vector: &C::Block,
+    padding: Padding,
+    buffer: &'a mut [u8],
+    msg_len: usize,
+    random: &mut R)
+    -> &'a mut [u8]
+{
+    // Since we only encrypt in blocks, we need to ensure the message length
+    // is an integer multiple of the block size.
+    let length = padding.pad_in_place_with(C::BLOCK_SIZE as u8, buffer, msg_len, random);
+
+    let mut prev_cipher_text = *init_vector;
+
+    for block in buffer[..length].chunks_exact_mut(C::BLOCK_SIZE) {
+        // Create an 'intermediate block' that is the result of XORing the
+        // previous encrypted block with the current plain-text block.
+        // In the case of the first block, we use the initialization vector
+        // as a a fake "previous encrypted block"
+        let mut intermediate_block = block_cipher::to_block::<C>(block);
+        xor_in_place(intermediate_block.as_mut(), prev_cipher_text.as_ref());
+
+        // encrypt the "intermediate block"
+        cipher.encrypt_block(&mut intermediate_block);
+
+        // The current cipher text block will be XORed against the next plain
+        // text block
+        prev_cipher_text = intermediate_block;
+
+        block.copy_from_slice(intermediate_block.as_ref());
+    }
+
+    &mut buffer[..length]
+}
+
+/// Decrypt a message with AES in Cipher Block Chaining mode
+///
+/// Fails if the message isn't a whole number of blocks, or if its padding is
+/// bad once decrypted.
+pub fn cbc_decrypt(
+    key: &Key,
+    init_vector: &Iv,
+    msg: &[u8])
+    -> Result<Vec<u8>, PaddingError>
+{
+    cbc_decrypt_with(&Aes128::with_key(key), init_vector.as_bytes(), Padding::Pkcs7, msg)
+}
+
+/// Decrypt a message with any block cipher and padding in Cipher Block
+/// Chaining mode
+pub fn cbc_decrypt_with<C: BlockCipher>(
+    cipher: &C,
+    init_vector: &C::Block,
+    padding: Padding,
+    msg: &[u8])
+    -> Result<Vec<u8>, PaddingError>
+{
+    let mut plain_text = msg.to_vec();
+
+    let length = cbc_decrypt_in_place_with(cipher, init_vector, padding, &mut plain_text)?.len();
+    plain_text.truncate(length);
+
+    Ok(plain_text)
+}
+
+/// Decrypt `buffer` with AES in Cipher Block Chaining mode, in place
+///
+/// This keys a new `Aes128` on every call, and with openssl that allocates.
+/// A padding oracle decrypts the same way thousands of times, so key one
+/// with `Aes128::with_key` up front and call `cbc_decrypt_in_place_with`.
+pub fn cbc_decrypt_in_place<'a>(
+    key: &Key,
+    init_vector: &Iv,
+    buffer: &'a mut [u8])
+    -> Result<&'a mut [u8], PaddingError>
+{
+    cbc_decrypt_in_place_with(&Aes128::with_key(key), init_vector.as_bytes(), Padding::Pkcs7, buffer)
+}
+
+/// Decrypt `buffer` with any block cipher and padding in Cipher Block
+/// Chaining mode, in place
+///
+/// Returns the plain text without its padding, which is the front of
+/// `buffer`. If the padding turns out to be bad, `buffer` is still left
+/// decrypted, padding and all. Like `cbc_encrypt_in_place_with`, this
+/// allocates nothing itself.
+pub fn cbc_decrypt_in_place_with<'a, C: BlockCipher>(
+    cipher: &C,
+    init_vector: &C::Block,
+    padding: Padding,
+    buffer: &'a mut [u8])
+    -> Result<&'a mut [u8], PaddingError>
+{
+    if buffer.len() % C::BLOCK_SIZE != 0 {
+        return Err(PaddingError::InvalidLength { length: buffer.len() });
+    }
+
+    let mut prev_cipher_text = *init_vector;
+
+    for block in buffer.chunks_exact_mut(C::BLOCK_SIZE) {
+        let cipher_text_block = block_cipher::to_block::<C>(block);
+
+        // Decrypt!
+        let mut intermediate_block = cipher_text_block;
+        cipher.decrypt_block(&mut intermediate_block);
+
+        // Xor our intermediate block with the previous cipher text block
+        xor_in_place(intermediate_block.as_mut(), prev_cipher_text.as_ref());
+
+        // The current cipher text block becomes the previous one
+        prev_cipher_text = cipher_text_block;
+
+        block.copy_from_slice(intermediate_block.as_ref());
+    }
+
+    // Remove padding that was added
+    let length = padding.unpadded_len(C::BLOCK_SIZE as u8, buffer)?;
+
+    Ok(&mut buffer[..length])
+}
+
+/// XOR `other` into `bytes`, without the `Vec` that `challenge02::xor_bytes`
+/// makes
+fn xor_in_place(bytes: &mut [u8], other: &[u8]) {
+    for (byte, other_byte) in bytes.iter_mut().zip(other) {
+        *byte ^= other_byte;
+    }
+}
+
+
+//////////////////////////////
+/* CIPHER MODES: CFB AND OFB */
+//////////////////////////////
+
+/// Encrypt a message with AES in (full-block) Cipher Feedback mode
+pub fn cfb128_encrypt(
+    key: &Key,
+    init_vector: &Iv,
+    msg: &[u8])
+    -> Vec<u8>
+{
+    cfb128_encrypt_with(&Aes128::with_key(key), init_vector.as_bytes(), msg)
+}
+
+/// Encrypt a message with any block cipher in full-block Cipher Feedback
+/// mode. That's CFB-128 for AES, CFB-64 for a cipher with 8-byte blocks.
+pub fn cfb128_encrypt_with<C: BlockCipher>(
+    cipher: &C,
+    init_vector: &C::Block,
+    msg: &[u8])
+    -> Vec<u8>
+{
+    let mut cipher_text = Vec::with_capacity(msg.len());
+
+    let mut prev_cipher_text = *init_vector;
+
+    for block in msg.chunks(C::BLOCK_SIZE) {
+        let mut keystream = prev_cipher_text;
+        cipher.encrypt_block(&mut keystream);
+
+        // The last block can be short. Only as much keystream as there is
+        // message gets used, and it doesn't matter what is left in
+        // `prev_cipher_text` since there is no next block
+        let cipher_text_block = &mut keystream.as_mut()[..block.len()];
+        xor_in_place(cipher_text_block, block);
+
+        prev_cipher_text.as_mut()[..block.len()].copy_from_slice(cipher_text_block);
+
+        cipher_text.extend_from_slice(cipher_text_block);
+    }
+
+    cipher_text
+}
+
+/// Decrypt a message with AES in (full-block) Cipher Feedback mode
+pub fn cfb128_decrypt(
+    key: &Key,
+    init_vector: &Iv,
+    msg: &[u8])
+    -> Vec<u8>
+{
+    cfb128_decrypt_with(&Aes128::with_key(key), init_vector.as_bytes(), msg)
+}
+
+/// Decrypt a message with any block cipher in full-block Cipher Feedback
+/// mode
+pub fn cfb128_decrypt_with<C: BlockCipher>(
+    cipher: &C,
+    init_vector: &C::Block,
+    msg: &[u8])
+    -> Vec<u8>
+{
+    let mut plain_text = Vec::with_capacity(msg.len());
+
+    let mut prev_cipher_text = *init_vector;
+
+    for block in msg.chunks(C::BLOCK_SIZE) {
+        // Still *en*crypting. CFB only ever uses the cipher in one direction
+        let mut keystream = prev_cipher_text;
+        cipher.encrypt_block(&mut keystream);
+
+        let plain_text_block = &mut keystream.as_mut()[..block.len()];
+        xor_in_place(plain_text_block, block);
+
+        plain_text.extend_from_slice(plain_text_block);
+
+        prev_cipher_text.as_mut()[..block.len()].copy_from_slice(block);
+    }
+
+    plain_text
+}
+
+/// Encrypt a message with AES in 8-bit Cipher Feedback mode
+pub fn cfb8_encrypt(
+    key: &Key,
+    init_vector: &Iv,
+    msg: &[u8])
+    -> Vec<u8>
+{
+    cfb8_encrypt_with(&Aes128::with_key(key), init_vector.as_bytes(), msg)
+}
+
+/// Encrypt a message with any block cipher in 8-bit Cipher Feedback mode
+pub fn cfb8_encrypt_with<C: BlockCipher>(
+    cipher: &C,
+    init_vector: &C::Block,
+    msg: &[u8])
+    -> Vec<u8>
+{
+    let mut cipher_text = Vec::with_capacity(msg.len());
+
+    // The last `C::BLOCK_SIZE` bytes of cipher text, starting out as the IV
+    let mut register = *init_vector;
+
+    for byte in msg {
+        let cipher_text_byte = byte ^ cfb8_keystream_byte(cipher, &register);
+
+        cfb8_shift_in(&mut register, cipher_text_byte);
+
+        cipher_text.push(cipher_text_byte);
+    }
+
+    cipher_text
+}
+
+/// Decrypt a message with AES in 8-bit Cipher Feedback mode
+pub fn cfb8_decrypt(
+    key: &Key,
+    init_vector: &Iv,
+    msg: &[u8])
+    -> Vec<u8>
+{
+    cfb8_decrypt_with(&Aes128::with_key(key), init_vector.as_bytes(), msg)
+}
+
+/// Decrypt a message with any block cipher in 8-bit Cipher Feedback mode
+pub fn cfb8_decrypt_with<C: BlockCipher>(
+    cipher: &C,
+    init_vector: &C::Block,
+    msg: &[u8])
+    -> Vec<u8>
+{
+    let mut plain_text = Vec::with_capacity(msg.len());
+
+    let mut register = *init_vector;
+
+    for byte in msg {
+        plain_text.push(byte ^ cfb8_keystream_byte(cipher, &register));
+
+        cfb8_shift_in(&mut register, *byte);
+    }
+
+    plain_text
+}
+
+/// The first byte of the encrypted shift register
+fn cfb8_keystream_byte<C: BlockCipher>(cipher: &C, register: &C::Block) -> u8 {
+    let mut keystream = *register;
+    cipher.encrypt_block(&mut keystream);
+
+    keystream.as_ref()[0]
+}
+
+/// Drop the first byte of the shift register and add `byte` at the end
+fn cfb8_shift_in<B: AsMut<[u8]>>(register: &mut B, byte: u8) {
+    let register = register.as_mut();
+
+    register.rotate_left(1);
+    *register.last_mut().unwrap() = byte;
+}
+
+/// Encrypt a message with AES in Output Feedback mode
+pub fn ofb_encrypt(
+    key: &Key,
+    init_vector: &Iv,
+    msg: &[u8])
+    -> Vec<u8>
+{
+    ofb_apply_with(&Aes128::with_key(key), init_vector.as_bytes(), msg)
+}
+
+/// Decrypt a message with AES in Output Feedback mode. Exactly the same as
+/// encrypting
+pub fn ofb_decrypt(
+    key: &Key,
+    init_vector: &Iv,
+    msg: &[u8])
+    -> Vec<u8>
+{
+    ofb_apply_with(&Aes128::with_key(key), init_vector.as_bytes(), msg)
+}
+
+/// Encrypt or decrypt a message with any block cipher in Output Feedback
+/// mode
+pub fn ofb_apply_with<C: BlockCipher>(
+    cipher: &C,
+    init_vector: &C::Block,
+    msg: &[u8])
+    -> Vec<u8>
+{
+    let mut output = Vec::with_capacity(msg.len());
+
+    let mut keystream = *init_vector;
+
+    for block in msg.chunks(C::BLOCK_SIZE) {
+        cipher.encrypt_block(&mut keystream);
+
+        // `keystream` is needed for the next block, so XOR into a copy
+        let mut output_block = keystream;
+        xor_in_place(&mut output_block.as_mut()[..block.len()], block);
+
+        output.extend_from_slice(&output_block.as_ref()[..block.len()]);
+    }
+
+    output
+}
+
+//////////////////////
+/* CIPHER MODE: ECB */
+//////////////////////
+
+
+/// Encrypt a message using the Electronic Code Book cipher mode
+pub fn ecb_encrypt(
+    key: &Key,
+    msg: &[u8]) 
+    -> Vec<u8>
+{
+    ecb_encrypt_with(&Aes128::with_key(key), Padding::Pkcs7, msg, &mut OsRandom)
+}
+
+/// Encrypt a message with any block cipher and padding using the Electronic
+/// Code Book cipher mode
+///
+/// `random` is only used by `Padding::Iso10126`, for its filler bytes.
+///
+/// # Panics
+///
+/// Will panic with `Padding::None` if `msg` isn't a whole number of blocks
+pub fn ecb_encrypt_with<C: BlockCipher, R: RandomSource + ?Sized>(
+    cipher: &C,
+    padding: Padding,
+    msg: &[u8],
+    random: &mut R)
+    -> Vec<u8>
+{
+    // Room for the message and up to a block of padding
+    let mut buffer = msg.to_vec();
+    buffer.resize(msg.len() + C::BLOCK_SIZE, 0);
+
+    let length = ecb_encrypt_in_place_with(cipher, padding, &mut buffer, msg.len(), random).len();
+    buffer.truncate(length);
+
+    buffer
+}
+
+/// Encrypt the first `msg_len` bytes of `buffer` using the Electronic Code
+/// Book cipher mode, in place
+///
+/// Keys a new `Aes128` every call, like `cbc_encrypt_in_place`. Hot loops
+/// should hold on to one and call `ecb_encrypt_in_place_with`.
+pub fn ecb_encrypt_in_place<'a>(
+    key: &Key,
+    buffer: &'a mut [u8],
+    msg_len: usize)
+    -> &'a mut [u8]
+{
+    ecb_encrypt_in_place_with(&Aes128::with_key(key), Padding::Pkcs7, buffer, msg_len, &mut OsRandom)
+}
+
+/// Encrypt the first `msg_len` bytes of `buffer` with any block cipher and
+/// padding using the Electronic Code Book cipher mode, in place
+///
+/// Works like `cbc_encrypt_in_place_with`: `buffer` needs room for the
+/// padding, and the cipher text is the front of it.
+///
+/// # Panics
+///
+/// Will panic if the padding doesn't fit in `buffer`, or with
+/// `Padding::None` if `msg_len` isn't a whole number of blocks
+pub fn ecb_encrypt_in_place_with<'a, C: BlockCipher, R: RandomSource + ?Sized>(
+    cipher: &C,
+    padding: Padding,
+    buffer: &'a mut [u8],
+    msg_len: usize,
+    random: &mut R)
+    -> &'a mut [u8]
+{
+    // Since we only encrypt in blocks, we need to ensure the message length
+    // is an integer multiple of the block size.
+    let length = padding.pad_in_place_with(C::BLOCK_SIZE as u8, buffer, msg_len, random);
+
+    // Now that i am are sure msg is an integer multiple of the block size,
+    // encrypt!
+    for block in buffer[..length].chunks_exact_mut(C::BLOCK_SIZE) {
+        let mut cipher_text_block = block_cipher::to_block::<C>(block);
+
+        cipher.encrypt_block(&mut cipher_text_block);
+
+        block.copy_from_slice(cipher_text_block.as_ref());
+    }
+
+    &mut buffer[..length]
+}
+
+/// Decrypt a message using the Electronic Code Book cipher mode
+///
+/// Fails if the message isn't a whole number of blocks, or if its padding is
+/// bad once decrypted.
+pub fn ecb_decrypt(
+    key: &Key,
+    msg: &[u8])
+    -> Result<Vec<u8>, PaddingError>
+{
+    ecb_decrypt_with(&Aes128::with_key(key), Padding::Pkcs7, msg)
+}
+
+/// Decrypt a message with any block cipher and padding using the Electronic
+/// Code Book cipher mode
+pub fn ecb_decrypt_with<C: BlockCipher>(
+    cipher: &C,
+    padding: Padding,
+    msg: &[u8])
+    -> Result<Vec<u8>, PaddingError>
+{
+    let mut plain_text = msg.to_vec();
+
+    let length = ecb_decrypt_in_place_with(cipher, padding, &mut plain_text)?.len();
+    plain_text.truncate(length);
+
+    Ok(plain_text)
+}
+
+/// Decrypt `buffer` using the Electronic Code Book cipher mode, in place
+///
+/// Keys a new `Aes128` every call, like `cbc_decrypt_in_place`. Hot loops
+/// should hold on to one and call `ecb_decrypt_in_place_with`.
+pub fn ecb_decrypt_in_place<'a>(
+    key: &Key,
+    buffer: &'a mut [u8])
+    -> Result<&'a mut [u8], PaddingError>
+{
+    ecb_decrypt_in_place_with(&Aes128::with_key(key), Padding::Pkcs7, buffer)
+}
+
+/// Decrypt `buffer` with any block cipher and padding using the Electronic
+/// Code Book cipher mode, in place
+///
+/// Works like `cbc_decrypt_in_place_with`: the plain text is the front of
+/// `buffer`, and bad padding leaves `buffer` decrypted anyway.
+pub fn ecb_decrypt_in_place_with<'a, C: BlockCipher>(
+    cipher: &C,
+    padding: Padding,
+    buffer: &'a mut [u8])
+    -> Result<&'a mut [u8], PaddingError>
+{
+    if buffer.len() % C::BLOCK_SIZE != 0 {
+        return Err(PaddingError::InvalidLength { length: buffer.len() });
+    }
+
+    for block in buffer.chunks_exact_mut(C::BLOCK_SIZE) {
+        let mut plain_text_block = block_cipher::to_block::<C>(block);
+
+        cipher.decrypt_block(&mut plain_text_block);
+
+        block.copy_from_slice(plain_text_block.as_ref());
+    }
+
+    // Unpad!
+    let length = padding.unpadded_len(C::BLOCK_SIZE as u8, buffer)?;
+
+    Ok(&mut buffer[..length])
+}
+
+//////////////////////
+/* CIPHER MODE: CTR */
+//////////////////////
+
+/// How a CTR counter block is put together from the nonce and the block
+/// number
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CounterLayout {
+    /// An 8-byte nonce, then the block number as a 64-bit little-endian
+    /// number. This is what cryptopals uses
+    Nonce64Counter64Le,
+
+    /// A 12-byte nonce, then the block number as a 32-bit big-endian number,
+    /// like GCM and NIST. The counter wraps around after 2^32 blocks
+    Nonce96Counter32Be,
+}
+
+impl CounterLayout {
+    /// How many bytes of nonce go in the counter block
+    pub fn nonce_len(self) -> usize {
+        match self {
+            CounterLayout::Nonce64Counter64Le => 8,
+            CounterLayout::Nonce96Counter32Be => 12,
+        }
+    }
+}
+
+/// Encrypt or decrypt (it's the same thing) a message with AES in counter
+/// mode, cryptopals style: an 8-byte nonce, then the counter as a 64-bit
+/// little endian number starting at 0
+///
+/// Cryptopals gives the nonce as a number, which is `Nonce::from_u64`.
+///
+/// # Panics
+///
+/// Will panic if `nonce` is not 8 bytes long. See `try_ctr_apply`
+pub fn ctr_apply(key: &Key, nonce: &Nonce, data: &[u8]) -> Vec<u8> {
+    try_ctr_apply(key, nonce, data).unwrap_or_else(|e| panic!("error: {}", e))
+}
+
+/// `ctr_apply`, but a nonce that isn't 8 bytes long is an error
+pub fn try_ctr_apply(key: &Key, nonce: &Nonce, data: &[u8]) -> Result<Vec<u8>, KeyError> {
+    let layout = CounterLayout::Nonce64Counter64Le;
+
+    if nonce.as_bytes().len() != layout.nonce_len() {
+        return Err(KeyError::WrongLength { expected: layout.nonce_len(), actual: nonce.as_bytes().len() });
+    }
+
+    Ok(ctr_apply_with(&Aes128::with_key(key), layout, nonce.as_bytes(), 0, data))
+}
+
+/// Encrypt or decrypt a message with any block cipher with 16-byte blocks
+/// in counter mode, with the counter block laid out like `layout` says,
+/// starting from byte `offset` of the keystream
+///
+/// `data` is taken to be the part of the message that starts at `offset`.
+/// For a message that comes in pieces, use `Ctr` directly.
+///
+/// # Panics
+///
+/// Will panic if `nonce` is not `layout.nonce_len()` bytes long
+pub fn ctr_apply_with<C: BlockCipher<Block = [u8; BLOCK_SIZE]>>(
+    cipher: &C,
+    layout: CounterLayout,
+    nonce: &[u8],
+    offset: u64,
+    data: &[u8])
+    -> Vec<u8>
+{
+    let mut ctr = Ctr::new(cipher, layout, nonce);
+    ctr.seek(offset);
+
+    let mut output = data.to_vec();
+
+    ctr.apply(&mut output);
+
+    output
+}
+
+/// A CTR keystream, for any block cipher with 16-byte blocks
+///
+/// `apply` XORs the keystream into the data and moves along, so a message
+/// can be fed in as many pieces as it comes in. `seek` jumps to any byte of
+/// the keystream.
+///
+/// The counter starts at 0. To start it somewhere else (GCM starts at 2,
+/// RFC 3686 at 1), `seek` to 16 times that.
+pub struct Ctr<'a, C> {
+    cipher: &'a C,
+    layout: CounterLayout,
+
+    /// The nonce, with room for the counter after it
+    counter_block: [u8; BLOCK_SIZE],
+
+    /// How far into the keystream we are, in bytes
+    offset: u64,
+
+    /// The keystream block that `offset` is in, if it has been computed yet
+    keystream: Option<(u64, [u8; BLOCK_SIZE])>,
+}
+
+impl<'a, C: BlockCipher<Block = [u8; BLOCK_SIZE]>> Ctr<'a, C> {
+    /// # Panics
+    ///
+    /// Will panic if `nonce` is not `layout.nonce_len()` bytes long
+    pub fn new(cipher: &'a C, layout: CounterLayout, nonce: &[u8]) -> Self {
+        assert_eq!(
+            layout.nonce_len(), nonce.len(),
+            "error: {:?} takes a {} byte nonce, got {}", layout, layout.nonce_len(), nonce.len()
+        );
+
+        let mut counter_block = [0u8; BLOCK_SIZE];
+        counter_block[..nonce.len()].copy_from_slice(nonce);
+
+        Ctr {
+            cipher,
+            layout,
+            counter_block,
+            offset: 0,
+            keystream: None,
+        }
+    }
+
+    /// Jump to byte `offset` of the keystream
+    pub fn seek(&mut self, offset: u64) {
+        self.offset = offset;
+    }
+
+    /// How far into the keystream we are, in bytes
+    pub fn position(&self) -> u64 {
+        self.offset
+    }
+
+    /// XOR the keystream into `data`, in place, and move past it
+    pub fn apply(&mut self, data: &mut [u8]) {
+        let mut done = 0;
+
+        while done < data.len() {
+            let block_number = self.offset / BLOCK_SIZE as u64;
+            let skip = (self.offset % BLOCK_SIZE as u64) as usize;
+
+            let keystream = self.keystream_block(block_number);
+
+            let count = (BLOCK_SIZE - skip).min(data.len() - done);
+
+            for (byte, key_byte) in data[done..done + count].iter_mut().zip(&keystream[skip..]) {
+                *byte ^= key_byte;
+            }
+
+            done += count;
+            self.offset += count as u64;
+        }
+    }
+
+    /// The keystream for block `block_number`, i.e. its encrypted counter
+    /// block
+    fn keystream_block(&mut self, block_number: u64) -> [u8; BLOCK_SIZE] {
+        if let Some((cached_number, keystream)) = self.keystream {
+            if cached_number == block_number {
+                return keystream;
+            }
+        }
+
+        let mut keystream = self.counter_block;
+
+        match self.layout {
+            CounterLayout::Nonce64Counter64Le => {
+                keystream[8..].copy_from_slice(&block_number.to_le_bytes());
+            },
+            CounterLayout::Nonce96Counter32Be => {
+                keystream[12..].copy_from_slice(&(block_number as u32).to_be_bytes());
+            },
+        }
+
+        self.cipher.encrypt_block(&mut keystream);
+
+        self.keystream = Some((block_number, keystream));
+
+        keystream
+    }
+}
+
+
+///////////////////////
+/* PADDING FUNCTIONS */
+///////////////////////
+
+
+/// Add padding to the input as ordained by PKCS#7
+/// The input is padded to to a multiple of the block size
+pub fn pkcs_pad(block_size: u8, input: &mut Vec<u8>) {
+    let count = block_size - (input.len() % block_size as usize) as u8;
+
+    let mut pad_bytes = vec![count; count as usize];
+
+    input.append(&mut pad_bytes);
+}
+
+/// Why a message's padding couldn't be removed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaddingError {
+    /// The message is empty, or not a whole number of blocks
+    InvalidLength { length: usize },
+
+    /// The padding byte at `offset` is wrong: either the last byte isn't
+    /// between 1 and the block size, or one of the bytes before it doesn't
+    /// match it
+    InvalidPadding { offset: usize },
+}
+
+impl PaddingError {
+    /// Where in the message the problem is. For a bad length, that's the end
+    pub fn offset(&self) -> usize {
+        match *self {
+            PaddingError::InvalidLength { length } => length,
+            PaddingError::InvalidPadding { offset } => offset,
+        }
+    }
+}
+
+impl fmt::Display for PaddingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            PaddingError::InvalidLength { length } => {
+                write!(f, "invalid padded length {}", length)
+            },
+            PaddingError::InvalidPadding { offset } => {
+                write!(f, "invalid padding byte at offset {}", offset)
+            },
+        }
+    }
+}
+
+impl error::Error for PaddingError {}
+
+/// Remove the PKCS#7 padding from the input
+///
+/// The last byte says how many bytes of padding there are, and every one of
+/// them has to be that same byte. "ICE ICE BABY\x04\x04\x04\x04" is fine,
+/// "ICE ICE BABY\x01\x02\x03\x04" is not. If the padding is bad, `input`
+/// is left alone.
+pub fn pkcs_unpad(block_size: u8, input: &mut Vec<u8>) -> Result<(), PaddingError> {
+    let length = pkcs_unpadded_len(block_size, input)?;
+
+    input.truncate(length);
+
+    Ok(())
+}
+
+/// How long `input` is without its PKCS#7 padding
+fn pkcs_unpadded_len(block_size: u8, input: &[u8]) -> Result<usize, PaddingError> {
+    // The input has to be a (non-zero) integer multiple of the block size
+    if input.is_empty() || input.len() % block_size as usize != 0 {
+        return Err(PaddingError::InvalidLength { length: input.len() });
+    }
+
+    // The value of last byte will correspond to how many padding bytes we have
+    let last_byte = *input.last().unwrap();
+
+    // Make sure the last byte is between 1 and the block size
+    if last_byte == 0 || last_byte > block_size {
+        return Err(PaddingError::InvalidPadding { offset: input.len() - 1 });
+    }
+
+    let start = input.len() - last_byte as usize;
+
+    if let Some(index) = input[start..].iter().position(|byte| *byte != last_byte) {
+        return Err(PaddingError::InvalidPadding { offset: start + index });
+    }
+
+    Ok(start)
+}
+
+/// The ways ECB and CBC messages get padded out to a whole number of blocks
+///
+/// Most of them end in a byte saying how much padding there is. Zero
+/// padding and `None` don't, so unpadding those can't tell padding from
+/// data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Padding {
+    /// `n` bytes of `n`: "ICE ICE BABY\x04\x04\x04\x04"
+    Pkcs7,
+
+    /// `n - 1` zeros, then `n`: "ICE ICE BABY\x00\x00\x00\x04"
+    AnsiX923,
+
+    /// A one bit, then zeros. As bytes: "ICE ICE BABY\x80\x00\x00\x00".
+    /// ISO/IEC 7816-4, also what CMAC does
+    Iso7816,
+
+    /// `n - 1` random bytes, then `n`. The random bytes aren't checked when
+    /// unpadding, since there's nothing to check them against. They come
+    /// from the `RandomSource` given to `pad_with` or the `_with` modes
+    Iso10126,
+
+    /// Zeros, only as many as it takes to fill the last block (so none at
+    /// all if it's already full). Unpadding strips the trailing zeros of the
+    /// last block, so messages that end in zeros don't survive
+    Zero,
+
+    /// Nothing. The message has to be a whole number of blocks already
+    None,
+}
+
+impl Padding {
+    /// Pad `input` to a multiple of `block_size`
+    ///
+    /// # Panics
+    ///
+    /// Will panic with `Padding::None` if `input` isn't a multiple of
+    /// `block_size` already
+    pub fn pad(self, block_size: u8, input: &mut Vec<u8>) {
+        self.pad_with(block_size, input, &mut OsRandom);
+    }
+
+    /// `pad`, with the random bytes of `Padding::Iso10126` coming from
+    /// `random`. The other schemes don't use it
+    ///
+    /// # Panics
+    ///
+    /// Will panic with `Padding::None` if `input` isn't a multiple of
+    /// `block_size` already
+    pub fn pad_with<R: RandomSource + ?Sized>(self, block_size: u8, input: &mut Vec<u8>, random: &mut R) {
+        let msg_len = input.len();
+
+        input.resize(msg_len + block_size as usize, 0);
+
+        let length = self.pad_in_place_with(block_size, input, msg_len, random);
+        input.truncate(length);
+    }
+
+    /// Pad the first `msg_len` bytes of `buffer` to a multiple of
+    /// `block_size`, using the rest of `buffer` for the padding. Returns the
+    /// padded length
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the padding doesn't fit in `buffer`, or with
+    /// `Padding::None` if `msg_len` isn't a multiple of `block_size` already
+    pub fn pad_in_place(self, block_size: u8, buffer: &mut [u8], msg_len: usize) -> usize {
+        self.pad_in_place_with(block_size, buffer, msg_len, &mut OsRandom)
+    }
+
+    /// `pad_in_place`, with the random bytes of `Padding::Iso10126` coming
+    /// from `random`
+    ///
+    /// # Panics
+    ///
+    /// Same as `pad_in_place`
+    pub fn pad_in_place_with<R: RandomSource + ?Sized>(
+        self,
+        block_size: u8,
+        buffer: &mut [u8],
+        msg_len: usize,
+        random: &mut R)
+        -> usize
+    {
+        let count = block_size - (msg_len % block_size as usize) as u8;
+
+        let length = match self {
+            Padding::Zero | Padding::None if count == block_size => msg_len,
+            Padding::None => {
+                panic!(
+                    "error: without padding, the input has to be a multiple of {} bytes, got {}",
+                    block_size, msg_len
+                );
+            },
+            _ => msg_len + count as usize,
+        };
+
+        assert!(
+            length <= buffer.len(),
+            "error: {} bytes padded take up {} bytes, but the buffer is only {}",
+            msg_len, length, buffer.len()
+        );
+
+        let padding = &mut buffer[msg_len..length];
+
+        match self {
+            Padding::Pkcs7 => {
+                for byte in padding.iter_mut() {
+                    *byte = count;
+                }
+            },
+            Padding::AnsiX923 | Padding::Iso10126 => {
+                let (filler, last) = padding.split_at_mut(count as usize - 1);
+
+                if self == Padding::AnsiX923 {
+                    for byte in filler.iter_mut() {
+                        *byte = 0;
+                    }
+                }
+                else {
+                    random.fill_bytes(filler);
+                }
+
+                last[0] = count;
+            },
+            Padding::Iso7816 => {
+                padding[0] = 0x80;
+
+                for byte in padding[1..].iter_mut() {
+                    *byte = 0;
+                }
+            },
+            Padding::Zero | Padding::None => {
+                for byte in padding.iter_mut() {
+                    *byte = 0;
+                }
+            },
+        }
+
+        length
+    }
+
+    /// Remove the padding from `input`, checking as much of it as there is
+    /// to check. If the padding is bad, `input` is left alone
+    pub fn unpad(self, block_size: u8, input: &mut Vec<u8>) -> Result<(), PaddingError> {
+        let length = self.unpadded_len(block_size, input)?;
+
+        input.truncate(length);
+
+        Ok(())
+    }
+
+    /// How long `input` is without its padding. This is `unpad` for when
+    /// `input` can't be truncated, e.g. because it's a slice
+    pub fn unpadded_len(self, block_size: u8, input: &[u8]) -> Result<usize, PaddingError> {
+        if self == Padding::Pkcs7 {
+            return pkcs_unpadded_len(block_size, input);
+        }
+
+        // Zero padding (and no padding) can be nothing at all, so an empty
+        // input is fine for them
+        let can_be_empty = self == Padding::Zero || self == Padding::None;
+
+        if (input.is_empty() && !can_be_empty) || input.len() % block_size as usize != 0 {
+            return Err(PaddingError::InvalidLength { length: input.len() });
+        }
+
+        let start = match self {
+            Padding::AnsiX923 | Padding::Iso10126 => {
+                let last_byte = *input.last().unwrap();
+
+                if last_byte == 0 || last_byte > block_size {
+                    return Err(PaddingError::InvalidPadding { offset: input.len() - 1 });
+                }
+
+                let start = input.len() - last_byte as usize;
+
+                if self == Padding::AnsiX923 {
+                    let filler = &input[start..input.len() - 1];
+
+                    if let Some(index) = filler.iter().position(|byte| *byte != 0) {
+                        return Err(PaddingError::InvalidPadding { offset: start + index });
+                    }
+                }
+
+                start
+            },
+            Padding::Iso7816 => {
+                // The 0x80 has to be in the last block, after nothing but
+                // zeros
+                let last_block = input.len() - block_size as usize;
+
+                let marker = input[last_block..].iter()
+                                                .rposition(|byte| *byte != 0)
+                                                .map(|index| last_block + index);
+
+                match marker {
+                    Some(offset) if input[offset] == 0x80 => offset,
+                    Some(offset) => return Err(PaddingError::InvalidPadding { offset }),
+                    None => return Err(PaddingError::InvalidPadding { offset: last_block }),
+                }
+            },
+            Padding::Zero => {
+                // Only the last block can be padding, however many zeros
+                // come before it
+                let last_block = input.len().saturating_sub(block_size as usize);
+
+                input[last_block..].iter()
+                                   .rposition(|byte| *byte != 0)
+                                   .map_or(last_block, |index| last_block + index + 1)
+            },
+            Padding::None | Padding::Pkcs7 => input.len(),
+        };
+
+        Ok(start)
+    }
+}
+
+
+///////////////////////////////////////////////////
+/* FUNCTIONS THAT OPERATE ON AN INDIVIDUAL BLOCK */
+///////////////////////////////////////////////////
+
+/// Encrypt a single 16-byte block using the AES algorithm, with a 128-bit
+/// key
+///
+/// This is `soft::encrypt_block` with the `soft-aes` feature, and
+/// `openssl_encrypt_block` otherwise. Either way the key schedule is redone
+/// on every call, so for more than the odd block, use `Aes128` (or `Aes192`,
+/// `Aes256` for the longer keys).
+pub fn encrypt_block(
+    key: &Key,
+    block: &[u8; BLOCK_SIZE])
+    -> [u8; BLOCK_SIZE]
+{
+    #[cfg(any(feature = "soft-aes", not(feature = "openssl")))]
+    return soft::encrypt_block(key.as_bytes(), block);
+
+    #[cfg(all(feature = "openssl", not(feature = "soft-aes")))]
+    return openssl_encrypt_block(key.as_bytes(), block);
+}
+
+/// Decrypt a 16-byte block using the AES algorithm, with a 128-bit key
+///
+/// This is `soft::decrypt_block` with the `soft-aes` feature, and
+/// `openssl_decrypt_block` otherwise.
+pub fn decrypt_block(
+    key: &Key,
+    block: &[u8; BLOCK_SIZE])
+    -> [u8; BLOCK_SIZE]
+{
+    #[cfg(any(feature = "soft-aes", not(feature = "openssl")))]
+    return soft::decrypt_block(key.as_bytes(), block);
+
+    #[cfg(all(feature = "openssl", not(feature = "soft-aes")))]
+    return openssl_decrypt_block(key.as_bytes(), block);
+}
+
+/// The openssl ECB cipher for a key of this length
+///
+/// # Panics
+///
+/// Will panic if `key` is not 16, 24 or 32 bytes long
+#[cfg(feature = "openssl")]
+fn openssl_ecb_cipher(key: &[u8]) -> symm::Cipher {
+    match key.len() {
+        16 => symm::Cipher::aes_128_ecb(),
+        24 => symm::Cipher::aes_192_ecb(),
+        32 => symm::Cipher::aes_256_ecb(),
+        len => panic!("error: an AES key is 16, 24 or 32 bytes, got {}", len),
+    }
+}
+
+/// An openssl ECB crypter for `key`, with padding disabled because the
+/// functions that use it take care of it
+#[cfg(feature = "openssl")]
+fn openssl_crypter(key: &[u8], mode: symm::Mode) -> symm::Crypter {
+    let mut crypter = symm::Crypter::new(
+        openssl_ecb_cipher(key),
+        mode,
+        key,
+        None).unwrap();
+
+    crypter.pad(false);
+
+    crypter
+}
+
+/// Run a single block through an openssl ECB crypter
+///
+/// The crypter can be used again for the next block: with padding disabled
+/// and whole blocks going in, openssl never holds anything back, so there is
+/// nothing to `finalize`.
+#[cfg(feature = "openssl")]
+fn openssl_crypt_block(
+    crypter: &mut symm::Crypter,
+    block: &[u8; BLOCK_SIZE])
+    -> [u8; BLOCK_SIZE]
+{
+    // `update` insists on room for an extra block, in case some input from
+    // an earlier call was held back. Nothing ever is (see above), so only
+    // the first block gets written to.
+    let mut output = [0u8; BLOCK_SIZE * 2];
+
+    let written = crypter.update(&block[..], &mut output[..]).unwrap();
+
+    assert_eq!(BLOCK_SIZE, written);
+
+    let mut result = [0u8; BLOCK_SIZE];
+    result.copy_from_slice(&output[..BLOCK_SIZE]);
+
+    result
+}
+
+/// Encrypt a single 16-byte block with openssl. The key size picks AES-128,
+/// AES-192 or AES-256
+///
+/// This sets up a new openssl context, key schedule and all, every time.
+/// To encrypt more than one block, `Aes::new` once and use that instead.
+///
+/// # Panics
+///
+/// Will panic if `key` is not 16, 24 or 32 bytes long
+#[cfg(feature = "openssl")]
+pub fn openssl_encrypt_block(
+    key: &[u8],
+    block: &[u8; BLOCK_SIZE])
+    -> [u8; BLOCK_SIZE]
+{
+    openssl_crypt_block(&mut openssl_crypter(key, symm::Mode::Encrypt), block)
+}
+
+/// Decrypt a 16-byte block with openssl. The key size picks AES-128, AES-192
+/// or AES-256
+///
+/// # Panics
+///
+/// Will panic if `key` is not 16, 24 or 32 bytes long
+#[cfg(feature = "openssl")]
+pub fn openssl_decrypt_block(
+    key: &[u8],
+    block: &[u8; BLOCK_SIZE])
+    -> [u8; BLOCK_SIZE] 
+{
+    openssl_crypt_block(&mut openssl_crypter(key, symm::Mode::Decrypt), block)
+}
+
+/// One of the three AES key sizes. Only `Bits128`, `Bits192` and `Bits256`
+/// implement it
+pub trait KeySize {
+    /// The key, e.g. `[u8; 32]` for `Bits256`
+    type Key: Copy + Default + AsRef<[u8]> + AsMut<[u8]>;
+}
+
+/// 128-bit keys: 10 rounds
+pub enum Bits128 {}
+
+/// 192-bit keys: 12 rounds
+pub enum Bits192 {}
+
+/// 256-bit keys: 14 rounds
+pub enum Bits256 {}
+
+impl KeySize for Bits128 {
+    type Key = [u8; 16];
+}
+
+impl KeySize for Bits192 {
+    type Key = [u8; 24];
+}
+
+impl KeySize for Bits256 {
+    type Key = [u8; 32];
+}
+
+/// AES with a `K`-sized key, as a `BlockCipher` for the generic modes
+///
+/// The key schedule is done once, in `new`, instead of for every block like
+/// the free `encrypt_block` and `decrypt_block` do. With openssl, that means
+/// holding on to an encrypting and a decrypting context. They need `&mut`
+/// to use, hence the `RefCell`s (and no `Sync`).
+pub struct Aes<K: KeySize> {
+    #[cfg(any(feature = "soft-aes", not(feature = "openssl")))]
+    round_keys: soft::RoundKeys,
+
+    #[cfg(all(feature = "openssl", not(feature = "soft-aes")))]
+    encrypter: RefCell<symm::Crypter>,
+
+    #[cfg(all(feature = "openssl", not(feature = "soft-aes")))]
+    decrypter: RefCell<symm::Crypter>,
+
+    key_size: PhantomData<K>,
+}
+
+/// AES with a 128-bit key
+pub type Aes128 = Aes<Bits128>;
+
+/// AES with a 192-bit key
+pub type Aes192 = Aes<Bits192>;
+
+/// AES with a 256-bit key
+pub type Aes256 = Aes<Bits256>;
+
+impl<K: KeySize> Aes<K> {
+    /// `new`, from a `Key` instead of a plain array
+    pub fn with_key(key: &Key<K>) -> Self {
+        Aes::new(key.as_bytes())
+    }
+}
+
+impl<K: KeySize> BlockCipher for Aes<K> {
+    const BLOCK_SIZE: usize = BLOCK_SIZE;
+    type Key = K::Key;
+    type Block = [u8; BLOCK_SIZE];
+
+    #[cfg(any(feature = "soft-aes", not(feature = "openssl")))]
+    fn new(key: &K::Key) -> Self {
+        Aes {
+            round_keys: soft::expand_key(key.as_ref()),
+            key_size: PhantomData,
+        }
+    }
+
+    #[cfg(all(feature = "openssl", not(feature = "soft-aes")))]
+    fn new(key: &K::Key) -> Self {
+        Aes {
+            encrypter: RefCell::new(openssl_crypter(key.as_ref(), symm::Mode::Encrypt)),
+            decrypter: RefCell::new(openssl_crypter(key.as_ref(), symm::Mode::Decrypt)),
+            key_size: PhantomData,
+        }
+    }
+
+    fn encrypt_block(&self, block: &mut [u8; BLOCK_SIZE]) {
+        #[cfg(any(feature = "soft-aes", not(feature = "openssl")))]
+        {
+            *block = soft::encrypt_block_with_round_keys(&self.round_keys, block);
+        }
+
+        #[cfg(all(feature = "openssl", not(feature = "soft-aes")))]
+        {
+            *block = openssl_crypt_block(&mut self.encrypter.borrow_mut(), block);
+        }
+    }
+
+    fn decrypt_block(&self, block: &mut [u8; BLOCK_SIZE]) {
+        #[cfg(any(feature = "soft-aes", not(feature = "openssl")))]
+        {
+            *block = soft::decrypt_block_with_round_keys(&self.round_keys, block);
+        }
+
+        #[cfg(all(feature = "openssl", not(feature = "soft-aes")))]
+        {
+            *block = openssl_crypt_block(&mut self.decrypter.borrow_mut(), block);
+        }
+    }
+}
+
+/// Generate a random 128-bit AES key
+pub fn get_random_key() -> Key {
+    Key::random()
+}
+
+/// Generate a 128-bit AES key from any source of random bytes
+pub fn get_random_key_with<R: RandomSource + ?Sized>(random: &mut R) -> Key {
+    Key::random_with(random)
+}
+
+/// Generate the bytes of a random AES key of any size, e.g.
+/// `random_key::<Bits256>()`. `Key::random` wraps them up properly
+pub fn random_key<K: KeySize>() -> K::Key {
+    random_key_with::<K, _>(&mut OsRandom)
+}
+
+/// `random_key`, from any source of random bytes
+///
+/// These are bare bytes, so nothing zeroes them. `Key::random_with` is the
+/// one to use for a key that has to stay secret.
+pub fn random_key_with<K: KeySize, R: RandomSource + ?Sized>(random: &mut R) -> K::Key {
+    let mut key = K::Key::default();
+
+    random.fill_bytes(key.as_mut());
+
+    key
+}
+
+/// Fill `buffer` with random bytes from the OS. See `random::OsRandom`
+pub fn fill_random(buffer: &mut [u8]) {
+    OsRandom.fill_bytes(buffer);
+}
+
+
+#[cfg(test)]
+pub mod test {
+    use std::convert::TryFrom;
+
+    use crate::aes128;
+    use crate::aes128::{Aes128, Aes192, Aes256, Bits256, Ctr, CounterLayout, Iv, Key, KeyError, Nonce, Padding, PaddingError};
+    use crate::block_cipher;
+    use crate::block_cipher::BlockCipher;
+    use crate::radix;
+    use crate::random::{OsRandom, SeededRandom};
+
+    #[test]
+    pub fn test_cbc_encrypt_then_decrypt() {
+        let input = b"YELLOW SUBMARINE";
+        let key = &Key::from_bytes(*b"YELLOW SUBMARINE");
+
+        let cipher_text = aes128::cbc_encrypt(
+            key,
+            &Iv::from_bytes([0; 16]),
+            input
+        );
+
+        // notice that because of padding, the cipher text is longer than the 
+        // input
+        assert_eq!(32, cipher_text.len());
+
+        assert_eq!(
+            "0apPZXiSZUL7tt2HbNIFCNyi2E9IlqAKtd7NEJ0c6pk=",
+            radix::bytes_to_base64(
+                &cipher_text[..]
+            )
+        );
+
+        // Decrypt
+        let plain_text = aes128::cbc_decrypt(
+            key,
+            &Iv::from_bytes([0; 16]),
+            &cipher_text[..]
+        ).unwrap();
+
+        assert_eq!(
+            "YELLOW SUBMARINE",
+            std::str::from_utf8(&plain_text).unwrap()
+        );
+    }
+
+    #[test]
+    pub fn test_ecb_encrypt_then_decrypt() {
+        let input = b"YELLOW SUBMARINE";
+        let key = &Key::from_bytes(*b"YELLOW SUBMARINE");
+
+        let cipher_text = aes128::ecb_encrypt(
+            key,
+            input
+        );
+
+        // notice that because of padding, the cipher text is longer than the 
+        // input
+        assert_eq!(32, cipher_text.len());
+
+        assert_eq!(
+            "0apPZXiSZUL7tt2HbNIFCGD6NnB+RfSZ26DyW5IjAaU=",
+            radix::bytes_to_base64(
+                &cipher_text[..]
+            )
+        );
+
+        // Decrypt
+        let plain_text = aes128::ecb_decrypt(
+            key,
+            &cipher_text[..]
+        ).unwrap();
+
+        assert_eq!(
+            "YELLOW SUBMARINE",
+            std::str::from_utf8(&plain_text).unwrap()
+        );
+    }
+
+    #[test]
+    pub fn test_encrypt_then_decrypt_block() {
+        let block = b"YELLOW SUBMARINE";
+        let key = &Key::from_bytes(*b"YELLOW SUBMARINE");
+
+        let cipher_text = aes128::encrypt_block(
+            key,
+            block
+        );
+
+        assert_eq!(
+            "0apPZXiSZUL7tt2HbNIFCA==",
+            radix::bytes_to_base64(
+                &cipher_text[..]
+            )
+        );
+
+        let decrypt_result = aes128::decrypt_block(
+            key,
+            &cipher_text
+        );
+
+        assert_eq!(
+            block,
+            &decrypt_result
+        );
+    }
+    
+    #[test]
+    pub fn test_longer_keys() {
+        // SP 800-38A F.1.3 and F.1.5: the first block of ECB-AES192 and
+        // ECB-AES256
+        let plain_text = radix::base16_to_bytes("6bc1bee22e409f96e93d7e117393172a");
+
+        let key = <[u8; 24]>::try_from(
+            &radix::base16_to_bytes("8e73b0f7da0e6452c810f32b809079e562f8ead2522c6b7b")[..]
+        ).unwrap();
+        let cipher = Aes192::new(&key);
+
+        let mut block = block_cipher::to_block::<Aes192>(&plain_text);
+        cipher.encrypt_block(&mut block);
+        assert_eq!("bd334f1d6e45f25ff712a214571fa5cc", radix::bytes_to_base16(&block));
+
+        let cipher_text = aes128::ecb_encrypt_with(&cipher, Padding::Pkcs7, &plain_text, &mut OsRandom);
+        assert_eq!(block.to_vec(), cipher_text[..16].to_vec());
+        assert_eq!(Ok(plain_text.clone()), aes128::ecb_decrypt_with(&cipher, Padding::Pkcs7, &cipher_text));
+
+        let key = <[u8; 32]>::try_from(
+            &radix::base16_to_bytes("603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4")[..]
+        ).unwrap();
+        let cipher = Aes256::new(&key);
+
+        let mut block = block_cipher::to_block::<Aes256>(&plain_text);
+        cipher.encrypt_block(&mut block);
+        assert_eq!("f3eed1bdb5d2a03c064b5a7e3db181f8", radix::bytes_to_base16(&block));
+
+        cipher.decrypt_block(&mut block);
+        assert_eq!(plain_text, block.to_vec());
+
+        // SP 800-38A F.2.5: the first block of CBC-AES256
+        let iv = block_cipher::to_block::<Aes256>(
+            &radix::base16_to_bytes("000102030405060708090a0b0c0d0e0f")
+        );
+        let cipher_text = aes128::cbc_encrypt_with(&cipher, &iv, Padding::Pkcs7, &plain_text, &mut OsRandom);
+        assert_eq!("f58c4c04d6e5f1ba779eabfb5f7bfbd6", radix::bytes_to_base16(&cipher_text[..16]));
+        assert_eq!(Ok(plain_text), aes128::cbc_decrypt_with(&cipher, &iv, Padding::Pkcs7, &cipher_text));
+
+        // A random key round trips too
+        let cipher = Aes256::with_key(&Key::<Bits256>::random());
+        let msg = b"Much of the data we have to decrypt";
+        let cipher_text = aes128::cbc_encrypt_with(&cipher, &iv, Padding::Pkcs7, msg, &mut OsRandom);
+        assert_eq!(Ok(msg.to_vec()), aes128::cbc_decrypt_with(&cipher, &iv, Padding::Pkcs7, &cipher_text));
+    }
+
+    #[test]
+    pub fn test_cipher_reuse() {
+        let key = b"YELLOW SUBMARINE";
+        let cipher = Aes128::new(key);
+
+        // One keyed cipher, lots of blocks, both directions interleaved
+        for i in 0..64u8 {
+            let plain_text = [i; 16];
+
+            let mut block = plain_text;
+            cipher.encrypt_block(&mut block);
+            assert_eq!(aes128::encrypt_block(&Key::from_bytes(*key), &plain_text), block);
+
+            cipher.decrypt_block(&mut block);
+            assert_eq!(plain_text, block);
+        }
+    }
+
+    /// SP 800-38A's example key, IV and (64-byte) plain text
+    fn sp800_38a_inputs() -> (Key, Iv, Vec<u8>) {
+        let key = Key::from_hex("2b7e151628aed2a6abf7158809cf4f3c");
+        let iv = Iv::from_hex("000102030405060708090a0b0c0d0e0f");
+
+        let plain_text = radix::base16_to_bytes(concat!(
+            "6bc1bee22e409f96e93d7e117393172a",
+            "ae2d8a571e03ac9c9eb76fac45af8e51",
+            "30c81c46a35ce411e5fbc1191a0a52ef",
+            "f69f2445df4f9b17ad2b417be66c3710",
+        ));
+
+        (key, iv, plain_text)
+    }
+
+    #[test]
+    pub fn test_cfb_ofb_vectors() {
+        let (key, iv, plain_text) = sp800_38a_inputs();
+
+        // F.3.13 CFB128-AES128.Encrypt
+        let cipher_text = aes128::cfb128_encrypt(&key, &iv, &plain_text);
+        assert_eq!(
+            concat!(
+                "3b3fd92eb72dad20333449f8e83cfb4a",
+                "c8a64537a0b3a93fcde3cdad9f1ce58b",
+                "26751f67a3cbb140b1808cf187a4f4df",
+                "c04b05357c5d1c0eeac4c66f9ff7f2e6",
+            ),
+            radix::bytes_to_base16(&cipher_text)
+        );
+        assert_eq!(plain_text, aes128::cfb128_decrypt(&key, &iv, &cipher_text));
+
+        // F.3.7 CFB8-AES128.Encrypt, which only goes 18 bytes in
+        let cipher_text = aes128::cfb8_encrypt(&key, &iv, &plain_text[..18]);
+        assert_eq!("3b79424c9c0dd436bace9e0ed4586a4f32b9", radix::bytes_to_base16(&cipher_text));
+        assert_eq!(plain_text[..18].to_vec(), aes128::cfb8_decrypt(&key, &iv, &cipher_text));
+
+        // F.4.1 OFB-AES128.Encrypt
+        let cipher_text = aes128::ofb_encrypt(&key, &iv, &plain_text);
+        assert_eq!(
+            concat!(
+                "3b3fd92eb72dad20333449f8e83cfb4a",
+                "7789508d16918f03f53c52dac54ed825",
+                "9740051e9c5fecf64344f7a82260edcc",
+                "304c6528f659c77866a510d9c1d6ae5e",
+            ),
+            radix::bytes_to_base16(&cipher_text)
+        );
+        assert_eq!(plain_text, aes128::ofb_decrypt(&key, &iv, &cipher_text));
+    }
+
+    #[test]
+    #[cfg(feature = "openssl")]
+    pub fn test_cfb_ofb_match_openssl() {
+        use openssl::symm;
+
+        let (key, iv, _) = sp800_38a_inputs();
+
+        // Lengths that end mid-block, and on a block boundary
+        for len in [0, 1, 15, 16, 17, 33, 100].iter() {
+            let msg = (0..*len).map(|i| (i * 13 + 5) as u8).collect::<Vec<u8>>();
+
+            let openssl = |cipher| symm::encrypt(cipher, key.as_bytes(), Some(iv.as_bytes()), &msg).unwrap();
+
+            assert_eq!(openssl(symm::Cipher::aes_128_cfb128()), aes128::cfb128_encrypt(&key, &iv, &msg));
+            assert_eq!(openssl(symm::Cipher::aes_128_cfb8()), aes128::cfb8_encrypt(&key, &iv, &msg));
+            assert_eq!(openssl(symm::Cipher::aes_128_ofb()), aes128::ofb_encrypt(&key, &iv, &msg));
+
+            let cipher_text = aes128::cfb128_encrypt(&key, &iv, &msg);
+            assert_eq!(msg, aes128::cfb128_decrypt(&key, &iv, &cipher_text));
+        }
+    }
+
+    #[test]
+    pub fn test_ctr() {
+        // Cryptopals challenge 18
+        let key = Key::from_bytes(*b"YELLOW SUBMARINE");
+        let cipher_text = radix::base64_to_bytes(
+            "L77na/nrFsKvynd6HzOoG7GHTLXsTVu9qvY/2syLXzhPweyyMTJULu/6/kXX0KSvoOLSFQ=="
+        );
+
+        let plain_text = aes128::ctr_apply(&key, &Nonce::from_u64(0), &cipher_text);
+
+        assert_eq!(
+            "Yo, VIP Let's kick it Ice, Ice, baby Ice, Ice, baby ",
+            std::str::from_utf8(&plain_text).unwrap()
+        );
+
+        assert_eq!(cipher_text, aes128::ctr_apply(&key, &Nonce::from_u64(0), &plain_text));
+    }
+
+    #[test]
+    pub fn test_ctr_nist_layout() {
+        // SP 800-38A F.5.1. The initial counter block is
+        // f0f1f2f3f4f5f6f7f8f9fafb fcfdfeff, i.e. the counter starts at
+        // 0xfcfdfeff
+        let (key, _, plain_text) = sp800_38a_inputs();
+        let nonce = radix::base16_to_bytes("f0f1f2f3f4f5f6f7f8f9fafb");
+
+        let mut data = plain_text.clone();
+
+        let expected = concat!(
+            "874d6191b620e3261bef6864990db6ce",
+            "9806f66b7970fdff8617187bb9fffdff",
+            "5ae4df3edbd5d35e5b4f09020db03eab",
+            "1e031dda2fbe03d1792170a0f3009cee",
+        );
+
+        let cipher = Aes128::with_key(&key);
+
+        let mut ctr = Ctr::new(&cipher, CounterLayout::Nonce96Counter32Be, &nonce);
+        ctr.seek(0xfcfdfeff * 16);
+
+        // In uneven pieces, to cross block boundaries mid-call
+        let (first, rest) = data.split_at_mut(5);
+        ctr.apply(first);
+        let (second, third) = rest.split_at_mut(30);
+        ctr.apply(second);
+        ctr.apply(third);
+
+        assert_eq!(expected, radix::bytes_to_base16(&data));
+        assert_eq!(0xfcfdfeff * 16 + 64, ctr.position());
+
+        // Seek back into the middle of the second block and decrypt from
+        // there
+        let mut tail = data[20..].to_vec();
+        ctr.seek(0xfcfdfeff * 16 + 20);
+        ctr.apply(&mut tail);
+
+        assert_eq!(plain_text[20..].to_vec(), tail);
+
+        // The same thing in one go
+        assert_eq!(
+            radix::base16_to_bytes(expected),
+            aes128::ctr_apply_with(&cipher, CounterLayout::Nonce96Counter32Be, &nonce, 0xfcfdfeff * 16, &plain_text)
+        );
+        assert_eq!(
+            plain_text[20..].to_vec(),
+            aes128::ctr_apply_with(&cipher, CounterLayout::Nonce96Counter32Be, &nonce, 0xfcfdfeff * 16 + 20, &data[20..])
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "nonce")]
+    pub fn test_ctr_wrong_nonce_len() {
+        Ctr::new(&Aes128::new(&[0; 16]), CounterLayout::Nonce96Counter32Be, &[0; 8]);
+    }
+
+    #[test]
+    pub fn test_try_ctr_apply() {
+        let key = Key::from_bytes(*b"YELLOW SUBMARINE");
+
+        assert_eq!(
+            Err(KeyError::WrongLength { expected: 8, actual: 12 }),
+            aes128::try_ctr_apply(&key, &Nonce::from_bytes(&[0; 12]), b"ICE ICE BABY")
+        );
+        assert_eq!(
+            Ok(aes128::ctr_apply(&key, &Nonce::from_u64(1), b"ICE ICE BABY")),
+            aes128::try_ctr_apply(&key, &Nonce::from_bytes(&1u64.to_le_bytes()), b"ICE ICE BABY")
+        );
+    }
+
+    #[test]
+    pub fn test_pad_unpad() {
+        let mut text = b"YELLOW SUBMA".to_vec();
+
+        aes128::pkcs_pad(
+            aes128::BLOCK_SIZE as u8,
+            &mut text
+        );
+
+        assert_eq!(
+            b"YELLOW SUBMA\x04\x04\x04\x04".to_vec(),
+            text
+        );
+
+        aes128::pkcs_unpad(
+            aes128::BLOCK_SIZE as u8,
+            &mut text
+        ).unwrap();
+
+        assert_eq!(
+            b"YELLOW SUBMA".to_vec(),
+            text
+        );
+    }
+
+    #[test]
+    pub fn test_strict_unpad() {
+        let unpad = |input: &[u8]| {
+            let mut input = input.to_vec();
+
+            aes128::pkcs_unpad(4, &mut input).map(|_| input)
+        };
+
+        assert_eq!(Ok(b"ICE ICE BABY".to_vec()), unpad(b"ICE ICE BABY\x04\x04\x04\x04"));
+        assert_eq!(Ok(b"ICE ICE BAB".to_vec()), unpad(b"ICE ICE BAB\x01"));
+
+        // Every padding byte is checked, not just the last
+        assert_eq!(
+            Err(PaddingError::InvalidPadding { offset: 12 }),
+            unpad(b"ICE ICE BABY\x01\x02\x03\x04")
+        );
+        assert_eq!(
+            Err(PaddingError::InvalidPadding { offset: 14 }),
+            unpad(b"ICE ICE BABY\x04\x04\x05\x04")
+        );
+
+        // The last byte is out of range
+        assert_eq!(Err(PaddingError::InvalidPadding { offset: 15 }), unpad(b"ICE ICE BABY\x05\x05\x05\x05"));
+        assert_eq!(Err(PaddingError::InvalidPadding { offset: 15 }), unpad(b"ICE ICE BABY\x00\x00\x00\x00"));
+
+        assert_eq!(Err(PaddingError::InvalidLength { length: 0 }), unpad(b""));
+        assert_eq!(Err(PaddingError::InvalidLength { length: 3 }), unpad(b"ICE"));
+    }
+
+    #[test]
+    pub fn test_padding_schemes() {
+        let pad = |padding: Padding, input: &[u8]| {
+            let mut input = input.to_vec();
+            padding.pad(4, &mut input);
+            input
+        };
+
+        let unpad = |padding: Padding, input: &[u8]| {
+            let mut input = input.to_vec();
+            padding.unpad(4, &mut input).map(|_| input)
+        };
+
+        let cases: [(Padding, &[u8], &[u8]); 11] = [
+            (Padding::Pkcs7, b"ICE ICE BABY", b"ICE ICE BABY\x04\x04\x04\x04"),
+            (Padding::AnsiX923, b"ICE ICE BABY", b"ICE ICE BABY\x00\x00\x00\x04"),
+            (Padding::AnsiX923, b"ICE ICE BAB", b"ICE ICE BAB\x01"),
+            (Padding::Iso7816, b"ICE ICE BABY", b"ICE ICE BABY\x80\x00\x00\x00"),
+            (Padding::Iso7816, b"ICE ICE BA", b"ICE ICE BA\x80\x00"),
+            (Padding::Iso7816, b"", b"\x80\x00\x00\x00"),
+            (Padding::Zero, b"ICE ICE BA", b"ICE ICE BA\x00\x00"),
+            (Padding::Zero, b"ICE ICE BABY", b"ICE ICE BABY"),
+            (Padding::Zero, b"", b""),
+            (Padding::None, b"ICE ICE BABY", b"ICE ICE BABY"),
+            (Padding::None, b"", b""),
+        ];
+
+        for (padding, input, padded) in cases.iter() {
+            assert_eq!(padded.to_vec(), pad(*padding, input), "{:?}", padding);
+            assert_eq!(Ok(input.to_vec()), unpad(*padding, padded), "{:?}", padding);
+        }
+
+        // The random fill is random, but the length byte isn't
+        let padded = pad(Padding::Iso10126, b"ICE ICE BA");
+        assert_eq!(12, padded.len());
+        assert_eq!(2, padded[11]);
+        assert_eq!(Ok(b"ICE ICE BA".to_vec()), unpad(Padding::Iso10126, &padded));
+
+        // Validation
+        assert_eq!(
+            Err(PaddingError::InvalidPadding { offset: 13 }),
+            unpad(Padding::AnsiX923, b"ICE ICE BABY\x00\x01\x00\x04")
+        );
+        assert_eq!(
+            Err(PaddingError::InvalidPadding { offset: 15 }),
+            unpad(Padding::Iso10126, b"ICE ICE BABY\x00\x01\x00\x05")
+        );
+        assert_eq!(
+            Err(PaddingError::InvalidPadding { offset: 13 }),
+            unpad(Padding::Iso7816, b"ICE ICE BABY\x80\x01\x00\x00")
+        );
+        assert_eq!(
+            Err(PaddingError::InvalidPadding { offset: 12 }),
+            unpad(Padding::Iso7816, b"ICE ICE BABY\x00\x00\x00\x00")
+        );
+        // Zeros in the blocks before the last one are data
+        assert_eq!(Ok(b"ICE\x00".to_vec()), unpad(Padding::Zero, b"ICE\x00\x00\x00\x00\x00"));
+
+        assert_eq!(Err(PaddingError::InvalidLength { length: 0 }), unpad(Padding::AnsiX923, b""));
+        assert_eq!(Err(PaddingError::InvalidLength { length: 3 }), unpad(Padding::None, b"ICE"));
+
+        // ISO 10126's random bytes can be replayed
+        let mut first = b"ICE ICE BABY".to_vec();
+        let mut again = first.clone();
+
+        Padding::Iso10126.pad_with(16, &mut first, &mut SeededRandom::new(7));
+        Padding::Iso10126.pad_with(16, &mut again, &mut SeededRandom::new(7));
+        assert_eq!(first, again);
+    }
+
+    #[test]
+    #[should_panic(expected = "multiple of 16")]
+    pub fn test_no_padding_needs_whole_blocks() {
+        aes128::ecb_encrypt_with(&Aes128::new(b"YELLOW SUBMARINE"), Padding::None, b"ICE ICE BABY", &mut OsRandom);
+    }
+
+    #[test]
+    pub fn test_modes_with_padding() {
+        let cipher = Aes128::new(b"YELLOW SUBMARINE");
+        let iv = [3; 16];
+        let msg = b"We interoperate with other systems";
+
+        let paddings = [
+            Padding::Pkcs7, Padding::AnsiX923, Padding::Iso7816, Padding::Iso10126, Padding::Zero,
+        ];
+
+        for padding in paddings.iter() {
+            let cipher_text = aes128::ecb_encrypt_with(&cipher, *padding, msg, &mut OsRandom);
+            assert_eq!(48, cipher_text.len());
+            assert_eq!(Ok(msg.to_vec()), aes128::ecb_decrypt_with(&cipher, *padding, &cipher_text));
+
+            let cipher_text = aes128::cbc_encrypt_with(&cipher, &iv, *padding, msg, &mut OsRandom);
+            assert_eq!(Ok(msg.to_vec()), aes128::cbc_decrypt_with(&cipher, &iv, *padding, &cipher_text));
+        }
+
+        // Raw blocks
+        let cipher_text = aes128::cbc_encrypt_with(&cipher, &iv, Padding::None, &msg[..32], &mut OsRandom);
+        assert_eq!(32, cipher_text.len());
+        assert_eq!(Ok(msg[..32].to_vec()), aes128::cbc_decrypt_with(&cipher, &iv, Padding::None, &cipher_text));
+
+        // PKCS#7 padded, but read as X.923
+        let cipher_text = aes128::cbc_encrypt(&Key::from_bytes(*b"YELLOW SUBMARINE"), &Iv::from_bytes(iv), msg);
+        assert_eq!(
+            Err(PaddingError::InvalidPadding { offset: 34 }),
+            aes128::cbc_decrypt_with(&cipher, &iv, Padding::AnsiX923, &cipher_text)
+        );
+    }
+
+    #[test]
+    pub fn test_iso10126_replays() {
+        let cipher = Aes128::new(b"YELLOW SUBMARINE");
+        let iv = [3; 16];
+
+        // The filler comes from the seed, so the cipher text is always the same
+        let cipher_text = aes128::ecb_encrypt_with(&cipher, Padding::Iso10126, b"ICE ICE BABY", &mut SeededRandom::new(7));
+        assert_eq!(radix::base16_to_bytes("53a44bd66909dd6c6cc1a0fded5918ae"), cipher_text);
+
+        let cipher_text = aes128::cbc_encrypt_with(&cipher, &iv, Padding::Iso10126, b"ICE ICE BABY", &mut SeededRandom::new(7));
+        assert_eq!(radix::base16_to_bytes("13e4aebb0d208ea77dd60647101b9726"), cipher_text);
+
+        let mut buffer = *b"ICE ICE BABY\0\0\0\0";
+        aes128::cbc_encrypt_in_place_with(&cipher, &iv, Padding::Iso10126, &mut buffer, 12, &mut SeededRandom::new(7));
+        assert_eq!(cipher_text, buffer.to_vec());
+    }
+
+    #[test]
+    pub fn test_decrypt_surfaces_padding_errors() {
+        let key = &Key::from_bytes(*b"YELLOW SUBMARINE");
+
+        // A block that decrypts to something ending in a zero byte
+        let cipher_text = aes128::encrypt_block(key, b"YELLOW SUBMARIN\x00");
+
+        assert_eq!(
+            Err(PaddingError::InvalidPadding { offset: 15 }),
+            aes128::ecb_decrypt(key, &cipher_text)
+        );
+        assert_eq!(
+            Err(PaddingError::InvalidPadding { offset: 15 }),
+            aes128::cbc_decrypt(key, &Iv::from_bytes([0; 16]), &cipher_text)
+        );
+
+        assert_eq!(
+            Err(PaddingError::InvalidLength { length: 15 }),
+            aes128::cbc_decrypt(key, &Iv::from_bytes([0; 16]), &cipher_text[..15])
+        );
+    }
+
+    #[test]
+    pub fn test_in_place() {
+        let key = &Key::from_bytes(*b"YELLOW SUBMARINE");
+        let cipher = Aes128::with_key(key);
+        let iv = Iv::from_bytes([3; 16]);
+
+        for length in [0, 1, 15, 16, 17, 48].iter() {
+            let msg = (0..*length).map(|i| i as u8 + 1).collect::<Vec<u8>>();
+
+            for padding in [Padding::Pkcs7, Padding::AnsiX923, Padding::Iso7816, Padding::Zero].iter() {
+                let mut buffer = [0u8; 64];
+                buffer[..msg.len()].copy_from_slice(&msg);
+
+                let cipher_text = aes128::cbc_encrypt_in_place_with(&cipher, iv.as_bytes(), *padding, &mut buffer, msg.len(), &mut OsRandom);
+                assert_eq!(aes128::cbc_encrypt_with(&cipher, iv.as_bytes(), *padding, &msg, &mut OsRandom), cipher_text.to_vec());
+
+                let plain_text = aes128::cbc_decrypt_in_place_with(&cipher, iv.as_bytes(), *padding, cipher_text).unwrap();
+                assert_eq!(msg, plain_text.to_vec());
+
+                let mut buffer = [0u8; 64];
+                buffer[..msg.len()].copy_from_slice(&msg);
+
+                let cipher_text = aes128::ecb_encrypt_in_place_with(&cipher, *padding, &mut buffer, msg.len(), &mut OsRandom);
+                assert_eq!(aes128::ecb_encrypt_with(&cipher, *padding, &msg, &mut OsRandom), cipher_text.to_vec());
+
+                let plain_text = aes128::ecb_decrypt_in_place_with(&cipher, *padding, cipher_text).unwrap();
+                assert_eq!(msg, plain_text.to_vec());
+            }
+        }
+
+        // The plain versions use PKCS#7, like `cbc_encrypt`
+        let mut buffer = *b"ICE ICE BABY\0\0\0\0";
+
+        aes128::cbc_encrypt_in_place(key, &iv, &mut buffer, 12);
+        assert_eq!(aes128::cbc_encrypt(key, &iv, b"ICE ICE BABY"), buffer.to_vec());
+
+        assert_eq!(
+            b"ICE ICE BABY",
+            aes128::cbc_decrypt_in_place(key, &iv, &mut buffer).unwrap()
+        );
+
+        aes128::ecb_encrypt_in_place(key, &mut buffer, 12);
+        aes128::ecb_decrypt_in_place(key, &mut buffer).unwrap();
+        assert_eq!(b"ICE ICE BABY\x04\x04\x04\x04", &buffer);
+
+        // Bad padding leaves the buffer decrypted
+        let mut buffer = aes128::encrypt_block(key, b"YELLOW SUBMARIN\x00");
+
+        assert_eq!(
+            Err(PaddingError::InvalidPadding { offset: 15 }),
+            aes128::ecb_decrypt_in_place(key, &mut buffer)
+        );
+        assert_eq!(b"YELLOW SUBMARIN\x00", &buffer);
+    }
+
+    #[test]
+    #[should_panic(expected = "the buffer is only 16")]
+    pub fn test_in_place_needs_room_for_padding() {
+        let mut buffer = *b"YELLOW SUBMARINE";
+
+        aes128::cbc_encrypt_in_place(&Key::from_bytes(*b"YELLOW SUBMARINE"), &Iv::from_bytes([0; 16]), &mut buffer, 16);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,515 @@
+//! # AES, from scratch
+//!
+//! The rest of `aes128` hands blocks to openssl, which is fast and correct
+//! but doesn't show anything. This is AES the way FIPS-197 describes it.
+//!
+//! The 16 bytes of a block are laid out in a 4x4 grid of bytes called the
+//! *state*, filled column by column:
+//!
+//! ```text
+//! in[0] in[4] in[8]  in[12]
+//! in[1] in[5] in[9]  in[13]
+//! in[2] in[6] in[10] in[14]
+//! in[3] in[7] in[11] in[15]
+//! ```
+//!
+//! So `state[row + 4 * column]` is just `in[row + 4 * column]`, and a
+//! `[u8; 16]` does the job.
+//!
+//! Encrypting is 10 (AES-128), 12 (AES-192) or 14 (AES-256) rounds of:
+//! - SubBytes: replace every byte using a fixed table (the S-box)
+//! - ShiftRows: rotate row `r` of the grid left by `r`
+//! - MixColumns: multiply every column by a fixed matrix
+//! - AddRoundKey: XOR in that round's key
+//!
+//! with an extra AddRoundKey at the start, and no MixColumns in the last
+//! round. The round keys all come out of the cipher key (key expansion).
+//! Decrypting does the inverse of every step, in reverse order.
+//!
+//! All of this is plain table lookups, so it is *not* constant time. Don't
+//! use it on secrets that matter.
+
+use std::ops::Deref;
+
+use crate::aes128::key;
+
+/// How many rounds AES-256 does. The other key sizes do fewer
+pub const MAX_ROUNDS: usize = 14;
+
+/// One key per round, plus one for the initial AddRoundKey
+///
+/// This derefs to a slice of just the round keys in use, so `round_keys[0]`
+/// and `round_keys.len()` work as expected.
+#[derive(Clone)]
+pub struct RoundKeys {
+    keys: [[u8; 16]; MAX_ROUNDS + 1],
+    rounds: usize,
+}
+
+impl RoundKeys {
+    /// How many rounds these keys are for
+    pub fn rounds(&self) -> usize {
+        self.rounds
+    }
+}
+
+/// The round keys give the key away just as well as the key itself
+impl Drop for RoundKeys {
+    fn drop(&mut self) {
+        for round_key in self.keys.iter_mut() {
+            key::zeroize(round_key);
+        }
+    }
+}
+
+impl Deref for RoundKeys {
+    type Target = [[u8; 16]];
+
+    fn deref(&self) -> &[[u8; 16]] {
+        &self.keys[..=self.rounds]
+    }
+}
+
+/// The S-box used by SubBytes. Computed at compile time from its
+/// definition (FIPS-197 §5.1.1), instead of pasting in 256 magic numbers
+pub static SBOX: [u8; 256] = sbox();
+
+/// The inverse S-box used by InvSubBytes
+pub static INV_SBOX: [u8; 256] = inv_sbox();
+
+/// The round constants used by key expansion: powers of x (i.e. 2) in GF(2^8)
+const RCON: [u8; 10] = [0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80, 0x1b, 0x36];
+
+/// Encrypt a single 16-byte block. The key size picks AES-128, AES-192 or
+/// AES-256
+///
+/// # Panics
+///
+/// Will panic if `key` is not 16, 24 or 32 bytes long
+pub fn encrypt_block(key: &[u8], block: &[u8; 16]) -> [u8; 16] {
+    encrypt_block_with_round_keys(&expand_key(key), block)
+}
+
+/// Decrypt a single 16-byte block. The key size picks AES-128, AES-192 or
+/// AES-256
+///
+/// # Panics
+///
+/// Will panic if `key` is not 16, 24 or 32 bytes long
+pub fn decrypt_block(key: &[u8], block: &[u8; 16]) -> [u8; 16] {
+    decrypt_block_with_round_keys(&expand_key(key), block)
+}
+
+/// Encrypt a single block with an already expanded key (FIPS-197 §5.1)
+pub fn encrypt_block_with_round_keys(round_keys: &RoundKeys, block: &[u8; 16]) -> [u8; 16] {
+    let rounds = round_keys.rounds();
+    let mut state = *block;
+
+    add_round_key(&mut state, &round_keys[0]);
+
+    for round_key in round_keys[1..rounds].iter() {
+        sub_bytes(&mut state);
+        shift_rows(&mut state);
+        mix_columns(&mut state);
+        add_round_key(&mut state, round_key);
+    }
+
+    // The last round skips MixColumns
+    sub_bytes(&mut state);
+    shift_rows(&mut state);
+    add_round_key(&mut state, &round_keys[rounds]);
+
+    state
+}
+
+/// Decrypt a single block with an already expanded key (FIPS-197 §5.3)
+pub fn decrypt_block_with_round_keys(round_keys: &RoundKeys, block: &[u8; 16]) -> [u8; 16] {
+    let rounds = round_keys.rounds();
+    let mut state = *block;
+
+    add_round_key(&mut state, &round_keys[rounds]);
+
+    for round_key in round_keys[1..rounds].iter().rev() {
+        inv_shift_rows(&mut state);
+        inv_sub_bytes(&mut state);
+        add_round_key(&mut state, round_key);
+        inv_mix_columns(&mut state);
+    }
+
+    inv_shift_rows(&mut state);
+    inv_sub_bytes(&mut state);
+    add_round_key(&mut state, &round_keys[0]);
+
+    state
+}
+
+/// Stretch the cipher key into one round key per round, plus one
+/// (FIPS-197 §5.2)
+///
+/// The key schedule works in 4-byte words. The first `nk` words are the key
+/// itself (`nk` is 4, 6 or 8). After that, every word is the word before it
+/// XORed with the word `nk` places back, except that every `nk`th word first
+/// gets rotated, run through the S-box and XORed with a round constant.
+/// AES-256 also runs the word halfway between those through the S-box.
+///
+/// # Panics
+///
+/// Will panic if `key` is not 16, 24 or 32 bytes long
+pub fn expand_key(key: &[u8]) -> RoundKeys {
+    assert!(
+        key.len() == 16 || key.len() == 24 || key.len() == 32,
+        "error: an AES key is 16, 24 or 32 bytes, got {}", key.len()
+    );
+
+    let nk = key.len() / 4;
+    let rounds = nk + 6;
+
+    let mut words = [[0u8; 4]; 4 * (MAX_ROUNDS + 1)];
+
+    for (word, key_word) in words.iter_mut().zip(key.chunks_exact(4)) {
+        word.copy_from_slice(key_word);
+    }
+
+    for i in nk..4 * (rounds + 1) {
+        let mut word = words[i - 1];
+
+        if i % nk == 0 {
+            // RotWord
+            word.rotate_left(1);
+
+            // SubWord
+            for byte in word.iter_mut() {
+                *byte = SBOX[*byte as usize];
+            }
+
+            word[0] ^= RCON[i / nk - 1];
+        }
+        else if nk > 6 && i % nk == 4 {
+            // SubWord only
+            for byte in word.iter_mut() {
+                *byte = SBOX[*byte as usize];
+            }
+        }
+
+        for (byte, previous) in word.iter_mut().zip(words[i - nk].iter()) {
+            *byte ^= previous;
+        }
+
+        words[i] = word;
+    }
+
+    let mut round_keys = RoundKeys {
+        keys: [[0u8; 16]; MAX_ROUNDS + 1],
+        rounds,
+    };
+
+    for (round_key, round_words) in round_keys.keys.iter_mut().zip(words.chunks_exact(4)) {
+        for (column, word) in round_key.chunks_exact_mut(4).zip(round_words.iter()) {
+            column.copy_from_slice(word);
+        }
+    }
+
+    // The schedule is as secret as the key
+    for word in words.iter_mut() {
+        key::zeroize(word);
+    }
+
+    round_keys
+}
+
+/// Replace every byte of the state with its S-box entry
+pub fn sub_bytes(state: &mut [u8; 16]) {
+    for byte in state.iter_mut() {
+        *byte = SBOX[*byte as usize];
+    }
+}
+
+pub fn inv_sub_bytes(state: &mut [u8; 16]) {
+    for byte in state.iter_mut() {
+        *byte = INV_SBOX[*byte as usize];
+    }
+}
+
+/// Rotate row `r` of the state left by `r` bytes
+pub fn shift_rows(state: &mut [u8; 16]) {
+    let old = *state;
+
+    for row in 1..4 {
+        for column in 0..4 {
+            state[row + 4 * column] = old[row + 4 * ((column + row) % 4)];
+        }
+    }
+}
+
+/// Rotate row `r` of the state right by `r` bytes
+pub fn inv_shift_rows(state: &mut [u8; 16]) {
+    let old = *state;
+
+    for row in 1..4 {
+        for column in 0..4 {
+            state[row + 4 * ((column + row) % 4)] = old[row + 4 * column];
+        }
+    }
+}
+
+/// Multiply every column, as a polynomial over GF(2^8), by
+/// 3x^3 + x^2 + x + 2 (FIPS-197 §5.1.3). In matrix form:
+///
+/// ```text
+/// 2 3 1 1
+/// 1 2 3 1
+/// 1 1 2 3
+/// 3 1 1 2
+/// ```
+pub fn mix_columns(state: &mut [u8; 16]) {
+    for column in state.chunks_exact_mut(4) {
+        let [a, b, c, d] = [column[0], column[1], column[2], column[3]];
+
+        column[0] = gf_mul(a, 2) ^ gf_mul(b, 3) ^ c ^ d;
+        column[1] = a ^ gf_mul(b, 2) ^ gf_mul(c, 3) ^ d;
+        column[2] = a ^ b ^ gf_mul(c, 2) ^ gf_mul(d, 3);
+        column[3] = gf_mul(a, 3) ^ b ^ c ^ gf_mul(d, 2);
+    }
+}
+
+/// The inverse of `mix_columns`: multiply by 11x^3 + 13x^2 + 9x + 14
+pub fn inv_mix_columns(state: &mut [u8; 16]) {
+    for column in state.chunks_exact_mut(4) {
+        let [a, b, c, d] = [column[0], column[1], column[2], column[3]];
+
+        column[0] = gf_mul(a, 14) ^ gf_mul(b, 11) ^ gf_mul(c, 13) ^ gf_mul(d, 9);
+        column[1] = gf_mul(a, 9) ^ gf_mul(b, 14) ^ gf_mul(c, 11) ^ gf_mul(d, 13);
+        column[2] = gf_mul(a, 13) ^ gf_mul(b, 9) ^ gf_mul(c, 14) ^ gf_mul(d, 11);
+        column[3] = gf_mul(a, 11) ^ gf_mul(b, 13) ^ gf_mul(c, 9) ^ gf_mul(d, 14);
+    }
+}
+
+/// XOR the round key into the state
+pub fn add_round_key(state: &mut [u8; 16], round_key: &[u8; 16]) {
+    for (byte, key_byte) in state.iter_mut().zip(round_key.iter()) {
+        *byte ^= key_byte;
+    }
+}
+
+/// Multiply two elements of GF(2^8), modulo the AES polynomial
+/// x^8 + x^4 + x^3 + x + 1
+///
+/// Shift-and-add: for every bit set in `b`, add (XOR) in `a` times that
+/// power of x. Multiplying `a` by x is a left shift, and if that overflows
+/// 8 bits, reducing by the polynomial is an XOR with 0x1b.
+pub const fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0;
+
+    while b != 0 {
+        if b & 1 != 0 {
+            product ^= a;
+        }
+
+        let overflow = a & 0x80 != 0;
+        a <<= 1;
+
+        if overflow {
+            a ^= 0x1b;
+        }
+
+        b >>= 1;
+    }
+
+    product
+}
+
+/// The multiplicative inverse in GF(2^8), with 0 mapping to 0
+///
+/// The non-zero elements form a group of order 255, so a^254 = a^-1
+const fn gf_inv(a: u8) -> u8 {
+    let mut inverse = 1;
+
+    let mut i = 0;
+    while i < 254 {
+        inverse = gf_mul(inverse, a);
+        i += 1;
+    }
+
+    // 0^254 would be 0 anyway, but the loop above started at 1
+    if a == 0 { 0 } else { inverse }
+}
+
+/// SubBytes is the inverse in GF(2^8), followed by an affine transform:
+/// b XOR (b rotated left by 1, 2, 3 and 4) XOR 0x63
+const fn sbox() -> [u8; 256] {
+    let mut table = [0u8; 256];
+
+    let mut i = 0;
+    while i < 256 {
+        let b = gf_inv(i as u8);
+
+        table[i] = b ^ b.rotate_left(1) ^ b.rotate_left(2) ^ b.rotate_left(3) ^ b.rotate_left(4) ^ 0x63;
+
+        i += 1;
+    }
+
+    table
+}
+
+const fn inv_sbox() -> [u8; 256] {
+    let forward = sbox();
+    let mut table = [0u8; 256];
+
+    let mut i = 0;
+    while i < 256 {
+        table[forward[i] as usize] = i as u8;
+        i += 1;
+    }
+
+    table
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use crate::aes128::soft;
+    use crate::radix;
+
+    fn block(hex: &str) -> [u8; 16] {
+        let mut block = [0u8; 16];
+        block.copy_from_slice(&radix::base16_to_bytes(hex));
+
+        block
+    }
+
+    #[test]
+    fn test_sbox() {
+        // FIPS-197 Figure 7
+        assert_eq!(0x63, soft::SBOX[0x00]);
+        assert_eq!(0x7c, soft::SBOX[0x01]);
+        assert_eq!(0xed, soft::SBOX[0x53]);
+        assert_eq!(0x16, soft::SBOX[0xff]);
+
+        for i in 0..=255u8 {
+            assert_eq!(i, soft::INV_SBOX[soft::SBOX[i as usize] as usize]);
+        }
+    }
+
+    #[test]
+    fn test_gf_mul() {
+        // FIPS-197 §4.2
+        assert_eq!(0xc1, soft::gf_mul(0x57, 0x83));
+        assert_eq!(0xfe, soft::gf_mul(0x57, 0x13));
+    }
+
+    #[test]
+    fn test_expand_key() {
+        // FIPS-197 Appendix A.1
+        let round_keys = soft::expand_key(&block("2b7e151628aed2a6abf7158809cf4f3c"));
+
+        assert_eq!(block("2b7e151628aed2a6abf7158809cf4f3c"), round_keys[0]);
+        assert_eq!(block("a0fafe1788542cb123a339392a6c7605"), round_keys[1]);
+        assert_eq!(block("d014f9a8c9ee2589e13f0cc8b6630ca6"), round_keys[10]);
+        assert_eq!(11, round_keys.len());
+
+        // Appendix A.2 and A.3: the last 4 words
+        let round_keys = soft::expand_key(&radix::base16_to_bytes(
+            "8e73b0f7da0e6452c810f32b809079e562f8ead2522c6b7b"
+        ));
+        assert_eq!(13, round_keys.len());
+        assert_eq!(block("e98ba06f448c773c8ecc720401002202"), round_keys[12]);
+
+        let round_keys = soft::expand_key(&radix::base16_to_bytes(
+            "603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4"
+        ));
+        assert_eq!(15, round_keys.len());
+        assert_eq!(block("fe4890d1e6188d0b046df344706c631e"), round_keys[14]);
+    }
+
+    #[test]
+    fn test_round_steps() {
+        // The first round of FIPS-197 Appendix B
+        let mut state = block("193de3bea0f4e22b9ac68d2ae9f84808");
+
+        soft::sub_bytes(&mut state);
+        assert_eq!(block("d42711aee0bf98f1b8b45de51e415230"), state);
+
+        soft::shift_rows(&mut state);
+        assert_eq!(block("d4bf5d30e0b452aeb84111f11e2798e5"), state);
+
+        soft::mix_columns(&mut state);
+        assert_eq!(block("046681e5e0cb199a48f8d37a2806264c"), state);
+
+        soft::add_round_key(&mut state, &block("a0fafe1788542cb123a339392a6c7605"));
+        assert_eq!(block("a49c7ff2689f352b6b5bea43026a5049"), state);
+
+        // And back again
+        soft::add_round_key(&mut state, &block("a0fafe1788542cb123a339392a6c7605"));
+
+        soft::inv_mix_columns(&mut state);
+        assert_eq!(block("d4bf5d30e0b452aeb84111f11e2798e5"), state);
+
+        soft::inv_shift_rows(&mut state);
+        assert_eq!(block("d42711aee0bf98f1b8b45de51e415230"), state);
+
+        soft::inv_sub_bytes(&mut state);
+        assert_eq!(block("193de3bea0f4e22b9ac68d2ae9f84808"), state);
+    }
+
+    #[test]
+    fn test_fips_197_vectors() {
+        // (key, plain text, cipher text) from Appendix B and Appendix C
+        let vectors = [
+            (
+                "2b7e151628aed2a6abf7158809cf4f3c",
+                "3243f6a8885a308d313198a2e0370734",
+                "3925841d02dc09fbdc118597196a0b32",
+            ),
+            (
+                "000102030405060708090a0b0c0d0e0f",
+                "00112233445566778899aabbccddeeff",
+                "69c4e0d86a7b0430d8cdb78070b4c55a",
+            ),
+            (
+                "000102030405060708090a0b0c0d0e0f1011121314151617",
+                "00112233445566778899aabbccddeeff",
+                "dda97ca4864cdfe06eaf70a0ec0d7191",
+            ),
+            (
+                "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+                "00112233445566778899aabbccddeeff",
+                "8ea2b7ca516745bfeafc49904b496089",
+            ),
+        ];
+
+        for (key, plain_text, cipher_text) in vectors.iter() {
+            let key = radix::base16_to_bytes(key);
+            let (plain_text, cipher_text) = (block(plain_text), block(cipher_text));
+
+            assert_eq!(cipher_text, soft::encrypt_block(&key, &plain_text));
+            assert_eq!(plain_text, soft::decrypt_block(&key, &cipher_text));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "openssl")]
+    fn test_matches_openssl() {
+        use crate::aes128;
+
+        // Not random, but all over the place
+        let mut plain_text = [0u8; 16];
+
+        for i in 0..200u8 {
+            // All three key sizes, in turn
+            let mut key = vec![0u8; [16, 24, 32][i as usize % 3]];
+
+            for (j, k) in key.iter_mut().enumerate() {
+                *k = i.wrapping_mul(31).wrapping_add(j as u8 * 7);
+            }
+
+            for (j, p) in plain_text.iter_mut().enumerate() {
+                *p = i.wrapping_mul(17) ^ (j as u8 * 13);
+            }
+
+            let cipher_text = aes128::openssl_encrypt_block(&key, &plain_text);
+
+            assert_eq!(cipher_text, soft::encrypt_block(&key, &plain_text));
+            assert_eq!(plain_text, soft::decrypt_block(&key, &cipher_text));
+            assert_eq!(plain_text, aes128::openssl_decrypt_block(&key, &cipher_text));
+        }
+    }
+}
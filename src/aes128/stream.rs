@@ -0,0 +1,757 @@
+//! Streaming ECB and CBC
+//!
+//! `ecb_encrypt`, `cbc_decrypt` and friends want the whole message in
+//! memory, and make a copy of it on top. The types here wrap an `io::Write`
+//! (encryptors) or an `io::Read` (decryptors) instead, and hold on to at most
+//! a block or so at a time, however big the file is.
+//!
+//! Encrypting, the last partial block is only padded once `finish` says no
+//! more is coming. Decrypting, the last block is held back until the inner
+//! reader runs out, since only then do we know it is the one with the
+//! padding in it.
+//!
+//! ```
+//! use std::io;
+//! use std::io::Write;
+//! use ez_cryptopals::aes128;
+//! use ez_cryptopals::aes128::{Iv, Key};
+//! use ez_cryptopals::aes128::stream::{CbcEncryptor, CbcDecryptor};
+//!
+//! let key = Key::from_bytes(*b"YELLOW SUBMARINE");
+//! let iv = Iv::from_bytes([0; 16]);
+//!
+//! let mut encryptor = CbcEncryptor::new(Vec::new(), &key, &iv);
+//! encryptor.write_all(b"I'm back and I'm ringin' the bell").unwrap();
+//! let cipher_text = encryptor.finish().unwrap();
+//!
+//! assert_eq!(aes128::cbc_encrypt(&key, &iv, b"I'm back and I'm ringin' the bell"), cipher_text);
+//!
+//! let mut decryptor = CbcDecryptor::new(&cipher_text[..], &key, &iv);
+//! let mut plain_text = Vec::new();
+//! io::copy(&mut decryptor, &mut plain_text).unwrap();
+//!
+//! assert_eq!(b"I'm back and I'm ringin' the bell".to_vec(), plain_text);
+//! ```
+//!
+//! The decryptors read from `inner` a block at a time, and the encryptors
+//! write to it in chunks of up to 4 KiB, so wrap files in a `BufReader` or
+//! `BufWriter`. Bad padding comes back as `io::ErrorKind::InvalidData`, with
+//! the `aes128::PaddingError` inside, from that read and every one after it.
+
+use std::io;
+use std::io::prelude::*;
+
+use crate::aes128::{Aes128, Iv, Key, Padding, PaddingError, xor_in_place};
+use crate::block_cipher;
+use crate::block_cipher::BlockCipher;
+use crate::random::{OsRandom, RandomSource};
+
+/// The size of the stack buffer the encryptors write out of
+const CHUNK_SIZE: usize = 4096;
+
+/// ECB and CBC differ only in what happens around each block
+#[derive(Clone, Copy)]
+enum Chaining<B> {
+    Ecb,
+
+    /// The previous block of cipher text (the IV to start with)
+    Cbc(B),
+}
+
+impl<B: Copy + AsRef<[u8]> + AsMut<[u8]>> Chaining<B> {
+    fn encrypt<C: BlockCipher<Block = B>>(&mut self, cipher: &C, block: &mut B) {
+        match self {
+            Chaining::Ecb => cipher.encrypt_block(block),
+            Chaining::Cbc(prev_cipher_text) => {
+                xor_in_place(block.as_mut(), prev_cipher_text.as_ref());
+                cipher.encrypt_block(block);
+
+                *prev_cipher_text = *block;
+            },
+        }
+    }
+
+    fn decrypt<C: BlockCipher<Block = B>>(&mut self, cipher: &C, block: &mut B) {
+        match self {
+            Chaining::Ecb => cipher.decrypt_block(block),
+            Chaining::Cbc(prev_cipher_text) => {
+                let cipher_text = *block;
+
+                cipher.decrypt_block(block);
+                xor_in_place(block.as_mut(), prev_cipher_text.as_ref());
+
+                *prev_cipher_text = cipher_text;
+            },
+        }
+    }
+}
+
+/// What both encryptors are made of
+struct Encryptor<W: Write, C: BlockCipher, R: RandomSource> {
+    inner: Option<W>,
+    cipher: C,
+    chaining: Chaining<C::Block>,
+    padding: Padding,
+    /// For `Padding::Iso10126`
+    random: R,
+    /// Bytes that do not make up a whole block yet
+    pending: C::Block,
+    pending_len: usize,
+    /// Set once `finish` has had a go at the tail, so that `Drop` doesn't
+    /// write a second one if it failed
+    finished: bool,
+}
+
+impl<W: Write, C: BlockCipher, R: RandomSource> Encryptor<W, C, R> {
+    fn new(inner: W, cipher: C, chaining: Chaining<C::Block>, padding: Padding, random: R) -> Self {
+        Encryptor {
+            inner: Some(inner),
+            cipher,
+            chaining,
+            padding,
+            random,
+            pending: C::Block::default(),
+            pending_len: 0,
+            finished: false,
+        }
+    }
+
+    fn finish(mut self) -> io::Result<W> {
+        self.finished = true;
+        self.write_tail()?;
+
+        Ok(self.inner.take().unwrap())
+    }
+
+    /// Pad and encrypt whatever is pending
+    fn write_tail(&mut self) -> io::Result<()> {
+        if self.padding == Padding::None && self.pending_len > 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "without padding, the input has to be a multiple of {} bytes, {} left over",
+                    C::BLOCK_SIZE, self.pending_len
+                )
+            ));
+        }
+
+        // Less than a block is ever pending, so the padding always fits in
+        // the rest of it
+        let mut tail = self.pending;
+        let length = self.padding.pad_in_place_with(C::BLOCK_SIZE as u8, tail.as_mut(), self.pending_len, &mut self.random);
+
+        self.write_chunk(&tail.as_ref()[..length])?;
+        self.pending_len = 0;
+
+        Ok(())
+    }
+
+    /// The most whole blocks `write_chunk` takes at a time
+    fn chunk_len() -> usize {
+        CHUNK_SIZE / C::BLOCK_SIZE * C::BLOCK_SIZE
+    }
+
+    /// Encrypt up to `chunk_len` bytes of whole blocks and write them out
+    ///
+    /// The chaining only moves on once `inner` has taken the lot, so after an
+    /// error the same blocks can be written again.
+    fn write_chunk(&mut self, blocks: &[u8]) -> io::Result<()> {
+        let mut chunk = [0u8; CHUNK_SIZE];
+        let mut chaining = self.chaining;
+
+        for (output, block) in chunk.chunks_exact_mut(C::BLOCK_SIZE).zip(blocks.chunks_exact(C::BLOCK_SIZE)) {
+            let mut block = block_cipher::to_block::<C>(block);
+
+            chaining.encrypt(&self.cipher, &mut block);
+
+            output.copy_from_slice(block.as_ref());
+        }
+
+        // `inner` is only ever taken by `finish`, which consumes the
+        // encryptor
+        self.inner.as_mut().unwrap().write_all(&chunk[..blocks.len()])?;
+        self.chaining = chaining;
+
+        Ok(())
+    }
+
+    /// Takes at most a chunk per call, and nothing at all if it fails
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // Top up the block left over from the previous call first
+        if self.pending_len > 0 {
+            let count = (C::BLOCK_SIZE - self.pending_len).min(buf.len());
+
+            let mut block = self.pending;
+            block.as_mut()[self.pending_len..self.pending_len + count].copy_from_slice(&buf[..count]);
+
+            if self.pending_len + count == C::BLOCK_SIZE {
+                self.write_chunk(block.as_ref())?;
+                self.pending_len = 0;
+            }
+            else {
+                self.pending = block;
+                self.pending_len += count;
+            }
+
+            return Ok(count);
+        }
+
+        let whole = (buf.len() - buf.len() % C::BLOCK_SIZE).min(Self::chunk_len());
+
+        if whole == 0 {
+            self.pending.as_mut()[..buf.len()].copy_from_slice(buf);
+            self.pending_len = buf.len();
+
+            return Ok(buf.len());
+        }
+
+        self.write_chunk(&buf[..whole])?;
+
+        Ok(whole)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.as_mut().unwrap().flush()
+    }
+}
+
+impl<W: Write, C: BlockCipher, R: RandomSource> Drop for Encryptor<W, C, R> {
+    fn drop(&mut self) {
+        if !self.finished {
+            let _ = self.write_tail();
+        }
+    }
+}
+
+/// What both decryptors are made of
+struct Decryptor<R: Read, C: BlockCipher> {
+    inner: R,
+    cipher: C,
+    chaining: Chaining<C::Block>,
+    padding: Padding,
+    /// Cipher text that does not make up a whole block yet
+    partial: C::Block,
+    partial_len: usize,
+    /// The latest decrypted block. It could be the last one, so its padding
+    /// might still have to come off
+    held: Option<C::Block>,
+    /// Plain text that can be read out
+    ready: C::Block,
+    ready_pos: usize,
+    ready_len: usize,
+    /// How many bytes have been read from `inner` so far
+    offset: usize,
+    eof: bool,
+    /// Bad padding at the end. Every read after that fails with it too,
+    /// rather than looking like a clean end of the stream
+    error: Option<PaddingError>,
+}
+
+impl<R: Read, C: BlockCipher> Decryptor<R, C> {
+    fn new(inner: R, cipher: C, chaining: Chaining<C::Block>, padding: Padding) -> Self {
+        Decryptor {
+            inner,
+            cipher,
+            chaining,
+            padding,
+            partial: C::Block::default(),
+            partial_len: 0,
+            held: None,
+            ready: C::Block::default(),
+            ready_pos: 0,
+            ready_len: 0,
+            offset: 0,
+            eof: false,
+            error: None,
+        }
+    }
+
+    /// Read more cipher text, and decrypt it if that completes a block
+    fn fill(&mut self) -> io::Result<()> {
+        let read = loop {
+            match self.inner.read(&mut self.partial.as_mut()[self.partial_len..]) {
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                result => break result?,
+            }
+        };
+
+        if read == 0 {
+            self.eof = true;
+
+            return self.unpad_held().map_err(|e| {
+                self.error = Some(e);
+
+                invalid_data(e)
+            });
+        }
+
+        self.partial_len += read;
+        self.offset += read;
+
+        if self.partial_len == C::BLOCK_SIZE {
+            self.partial_len = 0;
+
+            let mut block = self.partial;
+            self.chaining.decrypt(&self.cipher, &mut block);
+
+            // The block before this one wasn't the last after all
+            if let Some(previous) = self.held.replace(block) {
+                self.ready = previous;
+                self.ready_pos = 0;
+                self.ready_len = C::BLOCK_SIZE;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The inner reader ran out: take the padding off the last block
+    fn unpad_held(&mut self) -> Result<(), PaddingError> {
+        if self.partial_len > 0 {
+            return Err(PaddingError::InvalidLength { length: self.offset });
+        }
+
+        let mut last = match self.held.take() {
+            Some(block) => block.as_ref().to_vec(),
+            None => Vec::new(),
+        };
+
+        let last_start = self.offset - last.len();
+
+        self.padding.unpad(C::BLOCK_SIZE as u8, &mut last).map_err(|e| {
+            // Point into the whole stream, not just the last block
+            match e {
+                PaddingError::InvalidLength { length } => {
+                    PaddingError::InvalidLength { length: last_start + length }
+                },
+                PaddingError::InvalidPadding { offset } => {
+                    PaddingError::InvalidPadding { offset: last_start + offset }
+                },
+            }
+        })?;
+
+        self.ready.as_mut()[..last.len()].copy_from_slice(&last);
+        self.ready_pos = 0;
+        self.ready_len = last.len();
+
+        Ok(())
+    }
+
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if let Some(e) = self.error {
+            return Err(invalid_data(e));
+        }
+
+        while self.ready_pos == self.ready_len && !self.eof {
+            self.fill()?;
+        }
+
+        let count = (self.ready_len - self.ready_pos).min(buf.len());
+
+        buf[..count].copy_from_slice(&self.ready.as_ref()[self.ready_pos..self.ready_pos + count]);
+        self.ready_pos += count;
+
+        Ok(count)
+    }
+}
+
+fn invalid_data(e: PaddingError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+/// Encrypt everything written to it with CBC, and pass it on to `inner`
+///
+/// Call `finish` when done, to pad and write the last block. Dropping the
+/// encryptor will also try to finish, but any error is lost.
+pub struct CbcEncryptor<W: Write, C: BlockCipher = Aes128, R: RandomSource = OsRandom>(Encryptor<W, C, R>);
+
+impl<W: Write> CbcEncryptor<W> {
+    /// Encrypt with AES-128 and PKCS#7 padding, like `aes128::cbc_encrypt`
+    pub fn new(inner: W, key: &Key, init_vector: &Iv) -> Self {
+        CbcEncryptor::with_cipher(inner, Aes128::with_key(key), init_vector.as_bytes(), Padding::Pkcs7, OsRandom)
+    }
+}
+
+impl<W: Write, C: BlockCipher, R: RandomSource> CbcEncryptor<W, C, R> {
+    /// Encrypt with any block cipher and padding. `random` is only used by
+    /// `Padding::Iso10126`
+    pub fn with_cipher(inner: W, cipher: C, init_vector: &C::Block, padding: Padding, random: R) -> Self {
+        CbcEncryptor(Encryptor::new(inner, cipher, Chaining::Cbc(*init_vector), padding, random))
+    }
+
+    /// Pad and encrypt whatever is left over, and hand back the inner writer
+    ///
+    /// With `Padding::None`, anything left over is an
+    /// `io::ErrorKind::InvalidInput` error.
+    pub fn finish(self) -> io::Result<W> {
+        self.0.finish()
+    }
+}
+
+impl<W: Write, C: BlockCipher, R: RandomSource> Write for CbcEncryptor<W, C, R> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    /// Flush the inner writer
+    /// This can not flush a partial block. Only `finish` can do that.
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+/// Decrypt the CBC cipher text read from `inner`
+pub struct CbcDecryptor<R: Read, C: BlockCipher = Aes128>(Decryptor<R, C>);
+
+impl<R: Read> CbcDecryptor<R> {
+    /// Decrypt with AES-128 and PKCS#7 padding, like `aes128::cbc_decrypt`
+    pub fn new(inner: R, key: &Key, init_vector: &Iv) -> Self {
+        CbcDecryptor::with_cipher(inner, Aes128::with_key(key), init_vector.as_bytes(), Padding::Pkcs7)
+    }
+}
+
+impl<R: Read, C: BlockCipher> CbcDecryptor<R, C> {
+    /// Decrypt with any block cipher and padding
+    pub fn with_cipher(inner: R, cipher: C, init_vector: &C::Block, padding: Padding) -> Self {
+        CbcDecryptor(Decryptor::new(inner, cipher, Chaining::Cbc(*init_vector), padding))
+    }
+}
+
+impl<R: Read, C: BlockCipher> Read for CbcDecryptor<R, C> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+/// Encrypt everything written to it with ECB, and pass it on to `inner`
+///
+/// Call `finish` when done, to pad and write the last block. Dropping the
+/// encryptor will also try to finish, but any error is lost.
+pub struct EcbEncryptor<W: Write, C: BlockCipher = Aes128, R: RandomSource = OsRandom>(Encryptor<W, C, R>);
+
+impl<W: Write> EcbEncryptor<W> {
+    /// Encrypt with AES-128 and PKCS#7 padding, like `aes128::ecb_encrypt`
+    pub fn new(inner: W, key: &Key) -> Self {
+        EcbEncryptor::with_cipher(inner, Aes128::with_key(key), Padding::Pkcs7, OsRandom)
+    }
+}
+
+impl<W: Write, C: BlockCipher, R: RandomSource> EcbEncryptor<W, C, R> {
+    /// Encrypt with any block cipher and padding. `random` is only used by
+    /// `Padding::Iso10126`
+    pub fn with_cipher(inner: W, cipher: C, padding: Padding, random: R) -> Self {
+        EcbEncryptor(Encryptor::new(inner, cipher, Chaining::Ecb, padding, random))
+    }
+
+    /// Pad and encrypt whatever is left over, and hand back the inner writer
+    ///
+    /// With `Padding::None`, anything left over is an
+    /// `io::ErrorKind::InvalidInput` error.
+    pub fn finish(self) -> io::Result<W> {
+        self.0.finish()
+    }
+}
+
+impl<W: Write, C: BlockCipher, R: RandomSource> Write for EcbEncryptor<W, C, R> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    /// Flush the inner writer
+    /// This can not flush a partial block. Only `finish` can do that.
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+/// Decrypt the ECB cipher text read from `inner`
+pub struct EcbDecryptor<R: Read, C: BlockCipher = Aes128>(Decryptor<R, C>);
+
+impl<R: Read> EcbDecryptor<R> {
+    /// Decrypt with AES-128 and PKCS#7 padding, like `aes128::ecb_decrypt`
+    pub fn new(inner: R, key: &Key) -> Self {
+        EcbDecryptor::with_cipher(inner, Aes128::with_key(key), Padding::Pkcs7)
+    }
+}
+
+impl<R: Read, C: BlockCipher> EcbDecryptor<R, C> {
+    /// Decrypt with any block cipher and padding
+    pub fn with_cipher(inner: R, cipher: C, padding: Padding) -> Self {
+        EcbDecryptor(Decryptor::new(inner, cipher, Chaining::Ecb, padding))
+    }
+}
+
+impl<R: Read, C: BlockCipher> Read for EcbDecryptor<R, C> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use std::fs;
+    use std::io;
+    use std::io::prelude::*;
+    use std::path;
+
+    use crate::aes128;
+    use crate::aes128::{Aes128, Iv, Key, Padding, PaddingError};
+    use crate::aes128::stream::{CbcDecryptor, CbcEncryptor, EcbDecryptor, EcbEncryptor};
+    use crate::radix;
+    use crate::random::{OsRandom, SeededRandom};
+
+    fn key() -> Key {
+        Key::from_bytes(*b"YELLOW SUBMARINE")
+    }
+
+    fn iv() -> Iv {
+        Iv::from_bytes([5; 16])
+    }
+
+    /// A reader that hands out at most `n` bytes at a time
+    struct Trickle<'a>(&'a [u8], usize);
+
+    impl<'a> Read for Trickle<'a> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let count = self.1.min(buf.len()).min(self.0.len());
+
+            buf[..count].copy_from_slice(&self.0[..count]);
+            self.0 = &self.0[count..];
+
+            Ok(count)
+        }
+    }
+
+    #[test]
+    fn test_matches_one_shot() {
+        let (key, iv) = (key(), iv());
+
+        for len in [0, 1, 15, 16, 17, 100, 5000, 10000].iter() {
+            let msg = (0..*len).map(|i| (i * 7 + 3) as u8).collect::<Vec<u8>>();
+
+            // Written in odd-sized pieces
+            let mut encryptor = CbcEncryptor::new(Vec::new(), &key, &iv);
+            for piece in msg.chunks(7) {
+                encryptor.write_all(piece).unwrap();
+            }
+            let cipher_text = encryptor.finish().unwrap();
+            assert_eq!(aes128::cbc_encrypt(&key, &iv, &msg), cipher_text);
+
+            let mut plain_text = Vec::new();
+            CbcDecryptor::new(Trickle(&cipher_text, 5), &key, &iv).read_to_end(&mut plain_text).unwrap();
+            assert_eq!(msg, plain_text);
+
+            let mut encryptor = EcbEncryptor::new(Vec::new(), &key);
+            encryptor.write_all(&msg).unwrap();
+            let cipher_text = encryptor.finish().unwrap();
+            assert_eq!(aes128::ecb_encrypt(&key, &msg), cipher_text);
+
+            let mut plain_text = Vec::new();
+            EcbDecryptor::new(&cipher_text[..], &key).read_to_end(&mut plain_text).unwrap();
+            assert_eq!(msg, plain_text);
+        }
+    }
+
+    #[test]
+    fn test_challenge_file() {
+        let key = key();
+
+        let contents = fs::read_to_string(
+            path::PathBuf::from("./src/set02/input/_implement_cbc_mode.txt")
+        ).unwrap();
+
+        let cipher_text = radix::mime_base64_to_bytes(&contents);
+
+        let mut plain_text = String::new();
+        CbcDecryptor::new(&cipher_text[..], &key, &Iv::from_bytes([0; 16])).read_to_string(&mut plain_text).unwrap();
+
+        assert!(plain_text.starts_with("I'm back and I'm ringin' the bell"));
+    }
+
+    #[test]
+    fn test_other_padding() {
+        let (key, iv) = (key(), iv());
+
+        let cipher = Aes128::with_key(&key);
+        let msg = b"raw blocks, no padding at all!!!";
+
+        let mut encryptor = CbcEncryptor::with_cipher(Vec::new(), Aes128::with_key(&key), iv.as_bytes(), Padding::None, OsRandom);
+        encryptor.write_all(msg).unwrap();
+        let cipher_text = encryptor.finish().unwrap();
+        assert_eq!(aes128::cbc_encrypt_with(&cipher, iv.as_bytes(), Padding::None, msg, &mut OsRandom), cipher_text);
+
+        let mut plain_text = Vec::new();
+        CbcDecryptor::with_cipher(&cipher_text[..], Aes128::with_key(&key), iv.as_bytes(), Padding::None)
+            .read_to_end(&mut plain_text)
+            .unwrap();
+        assert_eq!(msg.to_vec(), plain_text);
+
+        // Not a whole number of blocks
+        let mut encryptor = EcbEncryptor::with_cipher(Vec::new(), Aes128::with_key(&key), Padding::None, OsRandom);
+        encryptor.write_all(&msg[..20]).unwrap();
+        assert_eq!(io::ErrorKind::InvalidInput, encryptor.finish().unwrap_err().kind());
+    }
+
+    #[test]
+    fn test_iso10126_replays() {
+        let (key, iv) = (key(), iv());
+
+        let cipher = Aes128::with_key(&key);
+        let msg = b"We interoperate with other systems";
+
+        // Lent, so the same source can go on to the next message
+        let mut random = SeededRandom::new(7);
+
+        let mut encryptor = CbcEncryptor::with_cipher(Vec::new(), Aes128::with_key(&key), iv.as_bytes(), Padding::Iso10126, &mut random);
+        encryptor.write_all(msg).unwrap();
+        let cipher_text = encryptor.finish().unwrap();
+
+        let mut expected_random = SeededRandom::new(7);
+        assert_eq!(aes128::cbc_encrypt_with(&cipher, iv.as_bytes(), Padding::Iso10126, msg, &mut expected_random), cipher_text);
+
+        let mut encryptor = EcbEncryptor::with_cipher(Vec::new(), Aes128::with_key(&key), Padding::Iso10126, &mut random);
+        encryptor.write_all(msg).unwrap();
+        let cipher_text = encryptor.finish().unwrap();
+
+        assert_eq!(aes128::ecb_encrypt_with(&cipher, Padding::Iso10126, msg, &mut expected_random), cipher_text);
+    }
+
+    #[test]
+    fn test_zero_padding_only_last_block() {
+        let key = key();
+
+        // Both blocks end in zeros. Only the last one can be padding
+        let mut msg = b"YELLOW SUBMARIN\x00".to_vec();
+        msg.extend_from_slice(&[0; 16]);
+
+        let cipher = Aes128::with_key(&key);
+        let cipher_text = aes128::ecb_encrypt_with(&cipher, Padding::Zero, &msg, &mut OsRandom);
+
+        assert_eq!(Ok(msg[..16].to_vec()), aes128::ecb_decrypt_with(&cipher, Padding::Zero, &cipher_text));
+
+        let mut plain_text = Vec::new();
+        EcbDecryptor::with_cipher(&cipher_text[..], Aes128::with_key(&key), Padding::Zero)
+            .read_to_end(&mut plain_text)
+            .unwrap();
+        assert_eq!(msg[..16].to_vec(), plain_text);
+    }
+
+    #[test]
+    fn test_errors() {
+        let (key, iv) = (key(), iv());
+
+        // Bad padding, reported where it is in the whole stream
+        let mut cipher_text = aes128::cbc_encrypt(&key, &iv, b"YELLOW SUBMARINE");
+        cipher_text.extend_from_slice(&aes128::encrypt_block(&key, b"YELLOW SUBMARIN\x00"));
+
+        let error = EcbDecryptor::new(&cipher_text[..], &key).read_to_end(&mut Vec::new()).unwrap_err();
+
+        assert_eq!(io::ErrorKind::InvalidData, error.kind());
+        assert_eq!(
+            Some(&PaddingError::InvalidPadding { offset: 47 }),
+            error.get_ref().unwrap().downcast_ref::<PaddingError>()
+        );
+
+        // Cut short
+        let error = CbcDecryptor::new(&cipher_text[..40], &key, &iv).read_to_end(&mut Vec::new()).unwrap_err();
+
+        assert_eq!(
+            Some(&PaddingError::InvalidLength { length: 40 }),
+            error.get_ref().unwrap().downcast_ref::<PaddingError>()
+        );
+    }
+
+    #[test]
+    fn test_padding_error_sticks() {
+        let key = key();
+
+        let mut cipher_text = aes128::ecb_encrypt(&key, b"YELLOW SUBMARINE");
+        cipher_text.extend_from_slice(&aes128::encrypt_block(&key, b"YELLOW SUBMARIN\x00"));
+
+        let mut decryptor = EcbDecryptor::new(&cipher_text[..], &key);
+        let mut buf = [0u8; 64];
+
+        // The first two blocks come out fine, then the last one is bad
+        let mut read = 0;
+        let error = loop {
+            match decryptor.read(&mut buf) {
+                Ok(count) => {
+                    assert_ne!(0, count);
+                    read += count;
+                },
+                Err(e) => break e,
+            }
+        };
+        assert_eq!(32, read);
+        assert_eq!(io::ErrorKind::InvalidData, error.kind());
+
+        // Reading again is not a clean end of the stream
+        let error = decryptor.read(&mut buf).unwrap_err();
+
+        assert_eq!(io::ErrorKind::InvalidData, error.kind());
+        assert_eq!(
+            Some(&PaddingError::InvalidPadding { offset: 47 }),
+            error.get_ref().unwrap().downcast_ref::<PaddingError>()
+        );
+        assert!(decryptor.read(&mut buf).is_err());
+    }
+
+    /// A writer that fails the first write, and keeps everything after that
+    struct FailOnce<'a> {
+        failed: bool,
+        written: &'a mut Vec<u8>,
+    }
+
+    impl<'a> Write for FailOnce<'a> {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if !self.failed {
+                self.failed = true;
+
+                return Err(io::Error::other("full"));
+            }
+
+            self.written.extend_from_slice(buf);
+
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_failed_write_can_be_retried() {
+        let (key, iv) = (key(), iv());
+
+        let msg = (0..100u8).collect::<Vec<u8>>();
+        let mut written = Vec::new();
+
+        let mut encryptor = CbcEncryptor::new(FailOnce { failed: false, written: &mut written }, &key, &iv);
+
+        // Not a whole block yet, so nothing reaches the inner writer
+        encryptor.write_all(&msg[..5]).unwrap();
+
+        // Completing the block does, and fails. Nothing was taken, so the
+        // same bytes go again
+        assert!(encryptor.write(&msg[5..]).is_err());
+        encryptor.write_all(&msg[5..]).unwrap();
+        encryptor.finish().unwrap();
+
+        assert_eq!(aes128::cbc_encrypt(&key, &iv, &msg), written);
+    }
+
+    #[test]
+    fn test_failed_finish_writes_nothing_more() {
+        let (key, iv) = (key(), iv());
+
+        let mut written = Vec::new();
+
+        let mut encryptor = CbcEncryptor::new(FailOnce { failed: false, written: &mut written }, &key, &iv);
+        encryptor.write_all(b"half a block").unwrap();
+        assert!(encryptor.finish().is_err());
+
+        // Dropping it after the error didn't pad and write another tail
+        assert!(written.is_empty());
+    }
+}
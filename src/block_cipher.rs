@@ -0,0 +1,129 @@
+//! # Block ciphers, in general
+//!
+//! ECB, CBC and the rest of the modes of operation don't care what the block
+//! cipher underneath them is. They need to know how big a block is and how
+//! to encrypt/decrypt one, and that's it.
+//!
+//! So instead of calling AES directly, the modes in `aes128` are written
+//! against the `BlockCipher` trait. AES is one implementation of it, but the
+//! same mode code (and the same attacks) can be run on top of a toy cipher,
+//! or on a cipher with 8-byte blocks.
+
+/// A keyed block cipher
+///
+/// A cipher is created once from its key with `new`, and then encrypts or
+/// decrypts one block at a time, in place.
+pub trait BlockCipher {
+    /// How many bytes a block is. Same as `Self::Block`'s length
+    const BLOCK_SIZE: usize;
+
+    /// What the cipher is keyed with, e.g. `[u8; 16]` for AES-128
+    type Key;
+
+    /// A single block, e.g. `[u8; 16]` for AES. `Default` gives a block of
+    /// zeros.
+    type Block: Copy + Default + AsRef<[u8]> + AsMut<[u8]>;
+
+    fn new(key: &Self::Key) -> Self;
+
+    fn encrypt_block(&self, block: &mut Self::Block);
+
+    fn decrypt_block(&self, block: &mut Self::Block);
+}
+
+/// Copy `bytes` into a block of `C`
+///
+/// # Panics
+///
+/// Will panic if `bytes` is not exactly `C::BLOCK_SIZE` long
+pub fn to_block<C: BlockCipher>(bytes: &[u8]) -> C::Block {
+    assert_eq!(
+        C::BLOCK_SIZE, bytes.len(),
+        "error: a block is {} bytes, got {}", C::BLOCK_SIZE, bytes.len()
+    );
+
+    let mut block = C::Block::default();
+    block.as_mut().copy_from_slice(bytes);
+
+    block
+}
+
+#[cfg(test)]
+pub mod test {
+    use crate::aes128;
+    use crate::aes128::Padding;
+    use crate::block_cipher::BlockCipher;
+    use crate::random::OsRandom;
+
+    /// A terrible cipher with 8-byte blocks: add the key to every byte, then
+    /// rotate the block by a byte. It is only here to make sure nothing in
+    /// the modes assumes AES or 16-byte blocks.
+    pub struct ToyCipher {
+        key: [u8; 8],
+    }
+
+    impl BlockCipher for ToyCipher {
+        const BLOCK_SIZE: usize = 8;
+        type Key = [u8; 8];
+        type Block = [u8; 8];
+
+        fn new(key: &[u8; 8]) -> Self {
+            ToyCipher { key: *key }
+        }
+
+        fn encrypt_block(&self, block: &mut [u8; 8]) {
+            for (byte, key_byte) in block.iter_mut().zip(self.key.iter()) {
+                *byte = byte.wrapping_add(*key_byte);
+            }
+
+            block.rotate_left(1);
+        }
+
+        fn decrypt_block(&self, block: &mut [u8; 8]) {
+            block.rotate_right(1);
+
+            for (byte, key_byte) in block.iter_mut().zip(self.key.iter()) {
+                *byte = byte.wrapping_sub(*key_byte);
+            }
+        }
+    }
+
+    #[test]
+    fn test_toy_cipher_block() {
+        let cipher = ToyCipher::new(&[1; 8]);
+
+        let mut block = *b"ABCDEFGH";
+
+        cipher.encrypt_block(&mut block);
+        assert_eq!(*b"CDEFGHIB", block);
+
+        cipher.decrypt_block(&mut block);
+        assert_eq!(*b"ABCDEFGH", block);
+    }
+
+    #[test]
+    fn test_modes_with_toy_cipher() {
+        let cipher = ToyCipher::new(b"toy key!");
+        let msg = b"Same mode code, different cipher";
+
+        // 32 bytes is 4 blocks, plus a whole block of padding
+        let cipher_text = aes128::ecb_encrypt_with(&cipher, Padding::Pkcs7, msg, &mut OsRandom);
+        assert_eq!(40, cipher_text.len());
+        assert_eq!(msg.to_vec(), aes128::ecb_decrypt_with(&cipher, Padding::Pkcs7, &cipher_text).unwrap());
+
+        let cipher_text = aes128::cbc_encrypt_with(&cipher, &[7; 8], Padding::Pkcs7, msg, &mut OsRandom);
+        assert_eq!(40, cipher_text.len());
+        assert_eq!(msg.to_vec(), aes128::cbc_decrypt_with(&cipher, &[7; 8], Padding::Pkcs7, &cipher_text).unwrap());
+
+        // The stream-like modes don't pad
+        let cipher_text = aes128::cfb128_encrypt_with(&cipher, &[7; 8], msg);
+        assert_eq!(32, cipher_text.len());
+        assert_eq!(msg.to_vec(), aes128::cfb128_decrypt_with(&cipher, &[7; 8], &cipher_text));
+
+        let cipher_text = aes128::cfb8_encrypt_with(&cipher, &[7; 8], msg);
+        assert_eq!(msg.to_vec(), aes128::cfb8_decrypt_with(&cipher, &[7; 8], &cipher_text));
+
+        let cipher_text = aes128::ofb_apply_with(&cipher, &[7; 8], msg);
+        assert_eq!(msg.to_vec(), aes128::ofb_apply_with(&cipher, &[7; 8], &cipher_text));
+    }
+}
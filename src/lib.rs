@@ -1,4 +1,7 @@
 pub mod radix;
+pub mod pem;
 pub mod aes128;
+pub mod block_cipher;
+pub mod random;
 pub mod set01;
 pub mod set02;
@@ -0,0 +1,217 @@
+//! Ascii85 (the Adobe flavour, as found in PDF and PostScript)
+//!
+//! Groups of 4 bytes are read as a 32-bit number and written out as 5 base 85
+//! digits, using the characters '!' (0) to 'u' (84). That is 25% overhead
+//! instead of base64's 33%.
+//!
+//! A few quirks:
+//! - A group of 4 zero bytes is written as a single 'z'
+//! - A final group of n < 4 bytes is padded with zeros, encoded, and only the
+//!   first n + 1 characters are kept. Decoding pads with 'u' and keeps n bytes
+//! - Whitespace can show up anywhere and is ignored
+//! - The data may be wrapped in `<~` and `~>`
+
+use crate::radix::DecodeError;
+
+/// '!' is the ascii85 zero digit
+const START_ASCII85_DIGIT: u8 = b'!';
+const END_ASCII85_DIGIT: u8 = START_ASCII85_DIGIT + 84;
+
+/// Stands in for a whole group of zero bytes
+const ASCII85_ZERO_GROUP: u8 = b'z';
+
+/// Convert a sequence of binary numbers to an Ascii85 representation
+///
+/// The output is not wrapped in `<~` and `~>`.
+pub fn bytes_to_ascii85(bytes: &[u8]) -> String {
+    let mut ascii85_characters = Vec::with_capacity(bytes.len().div_ceil(4) * 5);
+
+    for group in bytes.chunks(4) {
+        if group == [0, 0, 0, 0] {
+            ascii85_characters.push(ASCII85_ZERO_GROUP);
+            continue;
+        }
+
+        let mut padded = [0u8; 4];
+        padded[..group.len()].copy_from_slice(group);
+
+        let mut number = u32::from_be_bytes(padded);
+        let mut digits = [0u8; 5];
+
+        for digit in digits.iter_mut().rev() {
+            *digit = START_ASCII85_DIGIT + (number % 85) as u8;
+            number /= 85;
+        }
+
+        ascii85_characters.extend_from_slice(&digits[..group.len() + 1]);
+    }
+
+    // If the `unwrap` panics, that would be programmer (me) error
+    String::from_utf8(ascii85_characters).unwrap()
+}
+
+/// Convert an Ascii85 string to the sequence of binary numbers it represents
+///
+/// # Panics
+///
+/// Will panic if `ascii85_str` is not valid Ascii85. See
+/// `try_ascii85_to_bytes`
+pub fn ascii85_to_bytes(ascii85_str: &str) -> Vec<u8> {
+    try_ascii85_to_bytes(ascii85_str)
+        .unwrap_or_else(|e| panic!("error: invalid ascii85 string - {}", e))
+}
+
+/// Convert an Ascii85 string to the sequence of binary numbers it represents,
+/// failing with a `DecodeError` instead of panicking
+///
+/// The `<~` and `~>` delimiters are optional.
+pub fn try_ascii85_to_bytes(ascii85_str: &str) -> Result<Vec<u8>, DecodeError> {
+    let input = ascii85_str.as_bytes();
+
+    // Strip the delimiters, but remember where we started so error offsets
+    // still point into `ascii85_str`
+    let start = if input.starts_with(b"<~") { 2 } else { 0 };
+    let end = if input[start..].ends_with(b"~>") { input.len() - 2 } else { input.len() };
+
+    let mut bytes = Vec::with_capacity((end - start) / 5 * 4);
+
+    // The digits of the group being decoded, and where that group started
+    let mut group = [0u8; 5];
+    let mut group_len = 0;
+    let mut group_offset = start;
+
+    for (offset, ascii85_char) in input[start..end].iter().enumerate() {
+        let offset = start + offset;
+
+        if ascii85_char.is_ascii_whitespace() {
+            continue;
+        }
+
+        if *ascii85_char == ASCII85_ZERO_GROUP {
+            // 'z' is only allowed in place of a whole group
+            if group_len != 0 {
+                return Err(DecodeError::InvalidCharacter { byte: *ascii85_char, offset });
+            }
+
+            bytes.extend_from_slice(&[0, 0, 0, 0]);
+            continue;
+        }
+
+        if group_len == 0 {
+            group_offset = offset;
+        }
+
+        group[group_len] = ascii85_to_digit(ascii85_char, offset)?;
+        group_len += 1;
+
+        if group_len == 5 {
+            bytes.extend_from_slice(&group_to_bytes(&group, group_offset)?);
+            group_len = 0;
+        }
+    }
+
+    // A single leftover digit does not even make up one byte
+    if group_len == 1 {
+        return Err(DecodeError::InvalidLength { offset: input.len() });
+    }
+
+    if group_len > 1 {
+        // Pad with the highest digit, so that rounding goes the right way
+        for digit in group[group_len..].iter_mut() {
+            *digit = 84;
+        }
+
+        bytes.extend_from_slice(&group_to_bytes(&group, group_offset)?[..group_len - 1]);
+    }
+
+    Ok(bytes)
+}
+
+/// Turn 5 base 85 digits into the 4 bytes they stand for
+///
+/// 5 digits can go a bit beyond what fits in 32 bits ("s8W-!" is the
+/// largest that does), in which case we fail with an `Overflow` at `offset`
+fn group_to_bytes(group: &[u8; 5], offset: usize) -> Result<[u8; 4], DecodeError> {
+    let number = group.iter()
+                        .try_fold(0u32, |number, digit| {
+                            number.checked_mul(85)?.checked_add(*digit as u32)
+                        })
+                        .ok_or(DecodeError::Overflow { offset })?;
+
+    Ok(number.to_be_bytes())
+}
+
+/// Convert a single ascii85 character into the digit it stands for
+///
+/// `offset` is the position of the character in the original input, and is
+/// only used for reporting errors
+fn ascii85_to_digit(ascii85_char: &u8, offset: usize) -> Result<u8, DecodeError> {
+    match ascii85_char {
+        START_ASCII85_DIGIT..=END_ASCII85_DIGIT => Ok(ascii85_char - START_ASCII85_DIGIT),
+        _ => Err(DecodeError::InvalidCharacter { byte: *ascii85_char, offset }),
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use crate::radix;
+    use crate::radix::DecodeError;
+
+    #[test]
+    fn test_bytes_to_ascii85() {
+        assert_eq!("", radix::bytes_to_ascii85(b""));
+        assert_eq!("9jqo^", radix::bytes_to_ascii85(b"Man "));
+        assert_eq!("9jqo", radix::bytes_to_ascii85(b"Man"));
+        assert_eq!("9jn", radix::bytes_to_ascii85(b"Ma"));
+        assert_eq!("9`", radix::bytes_to_ascii85(b"M"));
+        assert_eq!("z", radix::bytes_to_ascii85(&[0, 0, 0, 0]));
+        assert_eq!("s8W-!", radix::bytes_to_ascii85(&[0xff; 4]));
+
+        // A partial group of zeros is not shortened to 'z'
+        assert_eq!("9jqo^z!!", radix::bytes_to_ascii85(b"Man \0\0\0\0\0"));
+
+        // Wikipedia's example
+        assert_eq!(
+            "9jqo^BlbD-BleB1DJ+*+F(f,q/0JhKF<GL>Cj@.4Gp$d7F!,L7@<6@)/0JDEF<G%<+EV:2F!,O<DJ+*.@<*K0@<6L(Df-\\0Ec5e;DffZ(EZee.Bl.9pF\"AGXBPCsi+DGm>@3BB/F*&OCAfu2/AKYi(DIb:@FD,*)+C]U=@3BN#EcYf8ATD3s@q?d$AftVqCh[NqF<G:8+EV:.+Cf>-FD5W8ARlolDIal(DId<j@<?3r@:F%a+D58'ATD4$Bl@l3De:,-DJs`8ARoFb/0JMK@qB4^F!,R<AKZ&-DfTqBG%G>uD.RTpAKYo'+CT/5+Cei#DII?(E,9)oF*2M7/c",
+            radix::bytes_to_ascii85(b"Man is distinguished, not only by his reason, but by this singular passion from other animals, which is a lust of the mind, that by a perseverance of delight in the continued and indefatigable generation of knowledge, exceeds the short vehemence of any carnal pleasure.")
+        );
+    }
+
+    #[test]
+    fn test_ascii85_to_bytes() {
+        assert_eq!(b"Man ".to_vec(), radix::ascii85_to_bytes("9jqo^"));
+        assert_eq!(b"Man".to_vec(), radix::ascii85_to_bytes("9jqo"));
+        assert_eq!(b"Ma".to_vec(), radix::ascii85_to_bytes("9jn"));
+        assert_eq!(b"M".to_vec(), radix::ascii85_to_bytes("9`"));
+        assert_eq!(vec![0xff; 4], radix::ascii85_to_bytes("s8W-!"));
+
+        // Delimiters, whitespace and 'z'
+        assert_eq!(
+            b"Man \0\0\0\0Man".to_vec(),
+            radix::ascii85_to_bytes("<~9jq\no^ z\r\n9jqo~>")
+        );
+    }
+
+    #[test]
+    fn test_try_ascii85_to_bytes() {
+        assert_eq!(
+            Err(DecodeError::InvalidCharacter { byte: b'v', offset: 4 }),
+            radix::try_ascii85_to_bytes("<~9jv~>")
+        );
+
+        assert_eq!(
+            Err(DecodeError::InvalidCharacter { byte: b'z', offset: 2 }),
+            radix::try_ascii85_to_bytes("9jzqo^")
+        );
+
+        assert_eq!(
+            Err(DecodeError::InvalidLength { offset: 6 }),
+            radix::try_ascii85_to_bytes("9jqo^9")
+        );
+
+        assert_eq!(
+            Err(DecodeError::Overflow { offset: 5 }),
+            radix::try_ascii85_to_bytes("9jqo^s8W-\"")
+        );
+    }
+}
@@ -0,0 +1,240 @@
+//! Base32 (RFC 4648 §6)
+//!
+//! Same idea as base64, but with 5-bit numbers (quintets) instead of 6-bit
+//! ones. 5 bytes make up 8 quintets, so the output is padded with `=` to a
+//! multiple of 8 characters.
+//!
+//! This is what TOTP secrets are written in. Those are usually handed out in
+//! lowercase and without padding, so the decoder accepts both.
+
+use crate::radix::{
+    DecodeError,
+    START_ASCII_DIGIT,
+    START_ASCII_UPALPHA, END_ASCII_UPALPHA,
+    START_ASCII_LOALPHA, END_ASCII_LOALPHA,
+};
+
+/// Base32 only uses the digits 2 to 7.
+/// 0 and 1 are left out so they can't be confused with O and I
+const START_ASCII_BASE32_DIGIT: u8 = START_ASCII_DIGIT + 2;
+const END_ASCII_BASE32_DIGIT: u8 = START_ASCII_DIGIT + 7;
+
+const START_BASE32_DIGIT: u8 = 26;
+
+/// Convert a sequence of binary numbers to a Base32 representation
+pub fn bytes_to_base32(bytes: &[u8]) -> String {
+    let mut base32_ascii_characters = Vec::with_capacity(bytes.len().div_ceil(5) * 8);
+
+    // Feed the bytes into a bit buffer, and take quintets off the top as soon
+    // as there are enough bits for one
+    let mut buffer: u16 = 0;
+    let mut bits = 0;
+
+    for byte in bytes {
+        buffer = (buffer << 8) | *byte as u16;
+        bits += 8;
+
+        while bits >= 5 {
+            bits -= 5;
+            base32_ascii_characters.push(quintet_to_base32(&(((buffer >> bits) & 0b1_1111) as u8)));
+        }
+    }
+
+    // Whatever is left gets filled up with zero bits
+    if bits > 0 {
+        base32_ascii_characters.push(quintet_to_base32(&(((buffer << (5 - bits)) & 0b1_1111) as u8)));
+    }
+
+    while base32_ascii_characters.len() % 8 != 0 {
+        base32_ascii_characters.push(b'=');
+    }
+
+    // If the `unwrap` panics, that would be programmer (me) error
+    String::from_utf8(base32_ascii_characters).unwrap()
+}
+
+/// Convert a base32 string to the sequence of binary numbers it represents
+///
+/// # Panics
+///
+/// Will panic if `base32_str` is not valid base32. See `try_base32_to_bytes`
+pub fn base32_to_bytes(base32_str: &str) -> Vec<u8> {
+    try_base32_to_bytes(base32_str)
+        .unwrap_or_else(|e| panic!("error: invalid base32 string - {}", e))
+}
+
+/// Convert a base32 string to the sequence of binary numbers it represents,
+/// failing with a `DecodeError` instead of panicking
+///
+/// Both upper and lower case are accepted. The padding can be left out, but
+/// if it is there it has to be right.
+pub fn try_base32_to_bytes(base32_str: &str) -> Result<Vec<u8>, DecodeError> {
+    let input = base32_str.as_bytes();
+
+    // Everything after the first padding character has to be padding too
+    let data_len = input.iter()
+                        .position(|c| *c == b'=')
+                        .unwrap_or(input.len());
+
+    if let Some(offset) = input[data_len..].iter().position(|c| *c != b'=') {
+        return Err(DecodeError::InvalidPadding { offset: data_len + offset });
+    }
+
+    // The last group of 8 characters can only hold 1 to 5 bytes. That is
+    // 2, 4, 5, 7 or 8 quintets. Anything else can't have come from an encoder
+    let is_padded = data_len < input.len();
+
+    if is_padded && input.len() % 8 != 0 {
+        return Err(DecodeError::InvalidLength { offset: input.len() });
+    }
+
+    if matches!(data_len % 8, 1 | 3 | 6) {
+        return Err(if is_padded {
+            DecodeError::InvalidPadding { offset: data_len }
+        }
+        else {
+            DecodeError::InvalidLength { offset: input.len() }
+        });
+    }
+
+    let mut bytes = Vec::with_capacity(data_len * 5 / 8);
+
+    let mut buffer: u16 = 0;
+    let mut bits = 0;
+
+    for (offset, base32_char) in input[..data_len].iter().enumerate() {
+        buffer = (buffer << 5) | base32_to_quintet(base32_char, offset)? as u16;
+        bits += 5;
+
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+
+    // The leftover bits were filled in by the encoder, so they must be zero
+    if buffer & ((1 << bits) - 1) != 0 {
+        return Err(DecodeError::NonCanonicalTrailingBits { offset: data_len - 1 });
+    }
+
+    Ok(bytes)
+}
+
+/// Convert a single base32 character into the quintet (5-bits) number it
+/// stands for
+///
+/// `offset` is the position of the character in the original input, and is
+/// only used for reporting errors
+fn base32_to_quintet(base32_char: &u8, offset: usize) -> Result<u8, DecodeError> {
+    match base32_char {
+        START_ASCII_UPALPHA..=END_ASCII_UPALPHA => Ok(base32_char - START_ASCII_UPALPHA),
+        START_ASCII_LOALPHA..=END_ASCII_LOALPHA => Ok(base32_char - START_ASCII_LOALPHA),
+        START_ASCII_BASE32_DIGIT..=END_ASCII_BASE32_DIGIT => {
+            Ok(base32_char - START_ASCII_BASE32_DIGIT + START_BASE32_DIGIT)
+        },
+        _ => Err(DecodeError::InvalidCharacter { byte: *base32_char, offset }),
+    }
+}
+
+/// Convert a quintet (5-bits) number to its base32 character
+///
+/// # Panics
+///
+/// Will panic if `num` uses more than 5 bits
+fn quintet_to_base32(num: &u8) -> u8 {
+    match num {
+        0..=25 => START_ASCII_UPALPHA + num,
+        26..=31 => START_ASCII_BASE32_DIGIT + num - START_BASE32_DIGIT,
+        _ => panic!("error: `num` is not a 5-bit number: {}", num),
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use crate::radix;
+    use crate::radix::DecodeError;
+    use crate::radix::base32;
+
+    // Test vectors from RFC 4648 §10
+    const VECTORS: [(&str, &str); 7] = [
+        ("", ""),
+        ("f", "MY======"),
+        ("fo", "MZXQ===="),
+        ("foo", "MZXW6==="),
+        ("foob", "MZXW6YQ="),
+        ("fooba", "MZXW6YTB"),
+        ("foobar", "MZXW6YTBOI======"),
+    ];
+
+    #[test]
+    fn test_bytes_to_base32() {
+        for (plain, encoded) in VECTORS.iter() {
+            assert_eq!(*encoded, radix::bytes_to_base32(plain.as_bytes()));
+        }
+    }
+
+    #[test]
+    fn test_base32_to_bytes() {
+        for (plain, encoded) in VECTORS.iter() {
+            assert_eq!(plain.as_bytes().to_vec(), radix::base32_to_bytes(encoded));
+        }
+
+        // Lowercase, unpadded TOTP-style secrets
+        assert_eq!(b"foobar".to_vec(), radix::base32_to_bytes("mzxw6ytboi"));
+    }
+
+    #[test]
+    fn test_try_base32_to_bytes() {
+        assert_eq!(
+            Err(DecodeError::InvalidCharacter { byte: b'1', offset: 2 }),
+            radix::try_base32_to_bytes("MZ1W6===")
+        );
+
+        assert_eq!(
+            Err(DecodeError::InvalidPadding { offset: 5 }),
+            radix::try_base32_to_bytes("MZXQ=A==")
+        );
+
+        assert_eq!(
+            Err(DecodeError::InvalidLength { offset: 7 }),
+            radix::try_base32_to_bytes("MZXQ===")
+        );
+
+        // 3 quintets can't come out of any number of bytes
+        assert_eq!(
+            Err(DecodeError::InvalidPadding { offset: 3 }),
+            radix::try_base32_to_bytes("MZX=====")
+        );
+        assert_eq!(
+            Err(DecodeError::InvalidLength { offset: 3 }),
+            radix::try_base32_to_bytes("MZX")
+        );
+
+        assert_eq!(
+            Err(DecodeError::NonCanonicalTrailingBits { offset: 1 }),
+            radix::try_base32_to_bytes("MZ======")
+        );
+    }
+
+    #[test]
+    fn test_quintet_to_base32() {
+        assert_eq!(b'A', base32::quintet_to_base32(&0));
+        assert_eq!(b'Z', base32::quintet_to_base32(&25));
+        assert_eq!(b'2', base32::quintet_to_base32(&26));
+        assert_eq!(b'7', base32::quintet_to_base32(&31));
+    }
+
+    #[test]
+    fn test_base32_to_quintet() {
+        assert_eq!(Ok(0), base32::base32_to_quintet(&b'A', 0));
+        assert_eq!(Ok(0), base32::base32_to_quintet(&b'a', 0));
+        assert_eq!(Ok(25), base32::base32_to_quintet(&b'Z', 0));
+        assert_eq!(Ok(26), base32::base32_to_quintet(&b'2', 0));
+        assert_eq!(Ok(31), base32::base32_to_quintet(&b'7', 0));
+
+        assert_eq!(
+            Err(DecodeError::InvalidCharacter { byte: b'8', offset: 3 }),
+            base32::base32_to_quintet(&b'8', 3)
+        );
+    }
+}
@@ -0,0 +1,220 @@
+//! Base58 (the Bitcoin alphabet)
+//!
+//! Unlike base16, base32 and base64, 58 is not a power of two. So the bytes
+//! can't be chopped up into fixed-size groups of bits. Instead, the whole
+//! input is treated as one big number, which is then written out in base 58,
+//! one digit at a time. That makes it quadratic, which is fine for the short
+//! IDs it is used for.
+//!
+//! The alphabet leaves out 0, O, I and l because they are easy to mix up.
+//! Leading zero bytes would disappear from a number, so each one is written
+//! as a leading '1' (the base58 zero digit).
+
+use crate::radix::{
+    DecodeError,
+    START_ASCII_DIGIT, END_ASCII_DIGIT,
+    START_ASCII_UPALPHA, END_ASCII_UPALPHA,
+    START_ASCII_LOALPHA, END_ASCII_LOALPHA,
+};
+
+// Where the gaps in the alphabet are
+const ASCII_UPPER_I: u8 = START_ASCII_UPALPHA + 8;
+const ASCII_UPPER_O: u8 = START_ASCII_UPALPHA + 14;
+const ASCII_LOWER_L: u8 = START_ASCII_LOALPHA + 11;
+
+// Where each contiguous run of the alphabet starts
+const START_BASE58_UPALPHA: u8 = 9;
+const START_BASE58_UPALPHA_J: u8 = 17;
+const START_BASE58_UPALPHA_P: u8 = 22;
+const START_BASE58_LOALPHA: u8 = 33;
+const START_BASE58_LOALPHA_M: u8 = 44;
+
+/// Convert a sequence of binary numbers to a Base58 representation
+pub fn bytes_to_base58(bytes: &[u8]) -> String {
+    let zeros = bytes.iter().take_while(|byte| **byte == 0).count();
+
+    // The base58 digits of the number, least significant first
+    let mut digits: Vec<u8> = Vec::with_capacity(bytes.len() * 138 / 100 + 1);
+
+    // Schoolbook multiplication: for every byte, digits = digits * 256 + byte
+    for byte in &bytes[zeros..] {
+        let mut carry = *byte as u32;
+
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+
+    let base58_ascii_characters = std::iter::repeat_n(0, zeros)
+            .chain(digits.into_iter().rev())
+            .map(|digit| digit_to_base58(&digit))
+            .collect::<Vec<u8>>();
+
+    // If the `unwrap` panics, that would be programmer (me) error
+    String::from_utf8(base58_ascii_characters).unwrap()
+}
+
+/// Convert a base58 string to the sequence of binary numbers it represents
+///
+/// # Panics
+///
+/// Will panic if `base58_str` is not valid base58. See `try_base58_to_bytes`
+pub fn base58_to_bytes(base58_str: &str) -> Vec<u8> {
+    try_base58_to_bytes(base58_str)
+        .unwrap_or_else(|e| panic!("error: invalid base58 string - {}", e))
+}
+
+/// Convert a base58 string to the sequence of binary numbers it represents,
+/// failing with a `DecodeError` instead of panicking
+pub fn try_base58_to_bytes(base58_str: &str) -> Result<Vec<u8>, DecodeError> {
+    let input = base58_str.as_bytes();
+
+    let zeros = input.iter().take_while(|c| **c == b'1').count();
+
+    // The bytes of the number, least significant first
+    let mut bytes: Vec<u8> = Vec::with_capacity(input.len() * 733 / 1000 + 1);
+
+    // The reverse of the above: for every digit, bytes = bytes * 58 + digit
+    for (offset, base58_char) in input.iter().enumerate().skip(zeros) {
+        let mut carry = base58_to_digit(base58_char, offset)? as u32;
+
+        for byte in bytes.iter_mut() {
+            carry += *byte as u32 * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+
+    Ok(
+        std::iter::repeat_n(0, zeros)
+            .chain(bytes.into_iter().rev())
+            .collect::<Vec<u8>>()
+    )
+}
+
+/// Convert a single base58 character into the digit it stands for
+///
+/// `offset` is the position of the character in the original input, and is
+/// only used for reporting errors
+fn base58_to_digit(base58_char: &u8, offset: usize) -> Result<u8, DecodeError> {
+    match *base58_char {
+        // No '0'
+        c @ START_ASCII_DIGIT..=END_ASCII_DIGIT if c != START_ASCII_DIGIT => {
+            Ok(c - START_ASCII_DIGIT - 1)
+        },
+
+        // No 'I' or 'O'
+        c @ START_ASCII_UPALPHA..=END_ASCII_UPALPHA if c != ASCII_UPPER_I && c != ASCII_UPPER_O => {
+            Ok(if c < ASCII_UPPER_I {
+                c - START_ASCII_UPALPHA + START_BASE58_UPALPHA
+            }
+            else if c < ASCII_UPPER_O {
+                c - (ASCII_UPPER_I + 1) + START_BASE58_UPALPHA_J
+            }
+            else {
+                c - (ASCII_UPPER_O + 1) + START_BASE58_UPALPHA_P
+            })
+        },
+
+        // No 'l'
+        c @ START_ASCII_LOALPHA..=END_ASCII_LOALPHA if c != ASCII_LOWER_L => {
+            Ok(if c < ASCII_LOWER_L {
+                c - START_ASCII_LOALPHA + START_BASE58_LOALPHA
+            }
+            else {
+                c - (ASCII_LOWER_L + 1) + START_BASE58_LOALPHA_M
+            })
+        },
+
+        _ => Err(DecodeError::InvalidCharacter { byte: *base58_char, offset }),
+    }
+}
+
+/// Convert a base58 digit to its character
+///
+/// # Panics
+///
+/// Will panic if `num` is 58 or more
+fn digit_to_base58(num: &u8) -> u8 {
+    match num {
+        0..=8 => START_ASCII_DIGIT + 1 + num,
+        9..=16 => START_ASCII_UPALPHA + num - START_BASE58_UPALPHA,
+        17..=21 => ASCII_UPPER_I + 1 + num - START_BASE58_UPALPHA_J,
+        22..=32 => ASCII_UPPER_O + 1 + num - START_BASE58_UPALPHA_P,
+        33..=43 => START_ASCII_LOALPHA + num - START_BASE58_LOALPHA,
+        44..=57 => ASCII_LOWER_L + 1 + num - START_BASE58_LOALPHA_M,
+        _ => panic!("error: `num` is not a base58 digit: {}", num),
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use crate::radix;
+    use crate::radix::DecodeError;
+    use crate::radix::base58;
+
+    // Test vectors from the Bitcoin Core test suite (base58_encode_decode.json)
+    const VECTORS: [(&[u8], &str); 8] = [
+        (b"", ""),
+        (&[0x61], "2g"),
+        (&[0x62, 0x62, 0x62], "a3gV"),
+        (&[0x63, 0x63, 0x63], "aPEr"),
+        (b"simply a long string", "2cFupjhnEsSn59qHXstmK2ffpLv2"),
+        (&[0x00, 0xeb, 0x15, 0x23, 0x1d, 0xfc, 0xeb, 0x60, 0x92, 0x58, 0x86, 0xb6, 0x7d, 0x06, 0x52, 0x99, 0x92, 0x59, 0x15, 0xae, 0xb1, 0x72, 0xc0, 0x66, 0x47], "1NS17iag9jJgTHD1VXjvLCEnZuQ3rJDE9L"),
+        (&[0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], "1111111111"),
+        (&[0xff; 4], "7YXq9G"),
+    ];
+
+    #[test]
+    fn test_bytes_to_base58() {
+        for (bytes, encoded) in VECTORS.iter() {
+            assert_eq!(*encoded, radix::bytes_to_base58(bytes));
+        }
+    }
+
+    #[test]
+    fn test_base58_to_bytes() {
+        for (bytes, encoded) in VECTORS.iter() {
+            assert_eq!(bytes.to_vec(), radix::base58_to_bytes(encoded));
+        }
+    }
+
+    #[test]
+    fn test_try_base58_to_bytes() {
+        assert_eq!(
+            Err(DecodeError::InvalidCharacter { byte: b'0', offset: 2 }),
+            radix::try_base58_to_bytes("2g0")
+        );
+
+        assert_eq!(
+            Err(DecodeError::InvalidCharacter { byte: b'l', offset: 0 }),
+            radix::try_base58_to_bytes("l")
+        );
+    }
+
+    #[test]
+    fn test_digit_to_base58_and_back() {
+        let alphabet = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+        for (digit, base58_char) in alphabet.iter().enumerate() {
+            assert_eq!(*base58_char, base58::digit_to_base58(&(digit as u8)));
+            assert_eq!(Ok(digit as u8), base58::base58_to_digit(base58_char, 0));
+        }
+
+        for c in b"0OIl+/" {
+            assert!(base58::base58_to_digit(c, 0).is_err());
+        }
+    }
+}
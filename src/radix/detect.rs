@@ -0,0 +1,293 @@
+//! Guessing how a blob of input was encoded
+//!
+//! The challenge files come in hex (challenge 4), base64 (6, 7, 10) and so
+//! on, and every test has to know which one it is looking at. Instead,
+//! `detect_encoding` takes a look at the bytes and makes a guess.
+//!
+//! The narrowest alphabet that decodes wins: hex, then base32, then base64.
+//! Anything that isn't text, or is text that none of those decode, is
+//! `Encoding::Binary`. Whitespace (line breaks especially) is ignored
+//! throughout.
+//!
+//! The guess comes with a confidence between 0 and 1. For the text encodings
+//! it is the chance that `n` characters from the next wider alphabet would
+//! *not* all happen to fit in the one we picked. "cafe" is perfectly good
+//! base64, so calling it hex is a coin toss. A full line of hex is not.
+
+use crate::radix;
+use crate::radix::{Base64Alphabet, Base64Config};
+
+/// What `detect_encoding` thinks the input is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// Base16, in either case, possibly broken up by whitespace
+    Hex,
+
+    /// Base64 in the given alphabet. `config.padding` is `false` when the
+    /// input length says the padding was left out
+    Base64 {
+        config: Base64Config,
+
+        /// Split across several lines, like the challenge files and MIME
+        wrapped: bool,
+    },
+
+    /// RFC 4648 base32, all upper or all lower case
+    Base32,
+
+    /// Not any of the above. Left as is by `decode_auto`
+    Binary,
+}
+
+/// The result of `detect_encoding`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Detection {
+    pub encoding: Encoding,
+
+    /// How sure the guess is, from 0 (not at all) to 1
+    pub confidence: f64,
+}
+
+/// How many characters of base64 are also hex digits
+const HEX_IN_BASE64: f64 = 22.0 / 64.0;
+
+/// How many characters of base64 are also (single-case) base32
+const BASE32_IN_BASE64: f64 = 32.0 / 64.0;
+
+/// How many printable ascii characters are also base64
+const BASE64_IN_PRINTABLE: f64 = 64.0 / 95.0;
+
+/// Guess the encoding of `input`
+pub fn detect_encoding(input: &[u8]) -> Detection {
+    detect(input).0
+}
+
+/// Decode `input` with whatever encoding `detect_encoding` settles on
+///
+/// `Encoding::Binary` input comes back unchanged. Only encodings that
+/// actually decode are ever picked, so this can't fail.
+pub fn decode_auto(input: &[u8]) -> Vec<u8> {
+    detect(input).1.unwrap_or_else(|| input.to_vec())
+}
+
+/// Detect the encoding, and hand back the decoded bytes too, since they
+/// come out of checking that the guess is right anyway
+fn detect(input: &[u8]) -> (Detection, Option<Vec<u8>>) {
+    let binary = |confidence| Detection { encoding: Encoding::Binary, confidence };
+
+    // Control characters and anything past ascii can't be in any of the
+    // text encodings
+    if input.iter().any(|c| !(c.is_ascii_graphic() || radix::is_base64_whitespace(c))) {
+        return (binary(1.0), None);
+    }
+
+    let significant = radix::without_base64_whitespace(input);
+
+    if significant.is_empty() {
+        return (binary(0.0), None);
+    }
+
+    // Padding is not a character that carries any information
+    let data_len = significant.iter().filter(|c| **c != b'=').count() as i32;
+
+    if let Some(bytes) = decode_hex(input, &significant) {
+        let detection = Detection {
+            encoding: Encoding::Hex,
+            confidence: 1.0 - HEX_IN_BASE64.powi(data_len),
+        };
+
+        return (detection, Some(bytes));
+    }
+
+    if let Some(bytes) = decode_base32(&significant) {
+        let detection = Detection {
+            encoding: Encoding::Base32,
+            confidence: 1.0 - BASE32_IN_BASE64.powi(data_len),
+        };
+
+        return (detection, Some(bytes));
+    }
+
+    if let Some((config, bytes)) = decode_base64(&significant) {
+        // Line breaks between the characters, as opposed to just a trailing
+        // one at the end of the file
+        let start = significant_start(input);
+        let end = significant_end(input);
+
+        let detection = Detection {
+            encoding: Encoding::Base64 {
+                config,
+                wrapped: input[start..end].contains(&b'\n'),
+            },
+            confidence: 1.0 - BASE64_IN_PRINTABLE.powi(data_len),
+        };
+
+        return (detection, Some(bytes));
+    }
+
+    // Text, but not in any encoding we know. The more it looks like base64,
+    // the more likely it is base64 that got mangled somewhere
+    let base64_like = significant.iter()
+                                    .filter(|c| {
+                                        **c == b'='
+                                            || radix::base64_to_sextet(c, 0, Base64Alphabet::Standard).is_ok()
+                                            || radix::base64_to_sextet(c, 0, Base64Alphabet::UrlSafe).is_ok()
+                                    })
+                                    .count();
+
+    (binary(1.0 - base64_like as f64 / significant.len() as f64), None)
+}
+
+/// Hex digits only, with every run between whitespace of even length
+fn decode_hex(input: &[u8], significant: &[u8]) -> Option<Vec<u8>> {
+    let even_runs = input.split(radix::is_base64_whitespace)
+                            .all(|run| run.len() % 2 == 0);
+
+    if !even_runs {
+        return None;
+    }
+
+    let mut bytes = vec![0; radix::base16_decoded_len(significant.len())];
+
+    radix::try_base16_to_bytes_into(significant, &mut bytes).ok()?;
+
+    Some(bytes)
+}
+
+/// Base32 of a single case
+fn decode_base32(significant: &[u8]) -> Option<Vec<u8>> {
+    let has_upper = significant.iter().any(u8::is_ascii_uppercase);
+    let has_lower = significant.iter().any(u8::is_ascii_lowercase);
+
+    if has_upper && has_lower {
+        return None;
+    }
+
+    // `significant` is all ascii
+    let base32_str = std::str::from_utf8(significant).unwrap();
+
+    radix::try_base32_to_bytes(base32_str).ok()
+}
+
+/// Base64 in whichever alphabet the special characters point to
+fn decode_base64(significant: &[u8]) -> Option<(Base64Config, Vec<u8>)> {
+    let standard = significant.iter().any(|c| *c == b'+' || *c == b'/');
+    let url_safe = significant.iter().any(|c| *c == b'-' || *c == b'_');
+
+    let alphabet = match (standard, url_safe) {
+        (true, true) => return None,
+        (false, true) => Base64Alphabet::UrlSafe,
+        _ => Base64Alphabet::Standard,
+    };
+
+    // A multiple of 4 is fine with the padding required, whether or not
+    // there actually is any
+    let config = Base64Config {
+        alphabet,
+        padding: significant.len() % 4 == 0,
+    };
+
+    let mut bytes = vec![0; radix::max_base64_decoded_len(significant.len())];
+
+    let written = radix::try_base64_to_bytes_into(significant, config, &mut bytes).ok()?;
+    bytes.truncate(written);
+
+    Some((config, bytes))
+}
+
+/// Where the first non-whitespace character is
+fn significant_start(input: &[u8]) -> usize {
+    input.iter()
+            .position(|c| !radix::is_base64_whitespace(c))
+            .unwrap_or(input.len())
+}
+
+/// One past the last non-whitespace character
+fn significant_end(input: &[u8]) -> usize {
+    input.iter()
+            .rposition(|c| !radix::is_base64_whitespace(c))
+            .map_or(0, |offset| offset + 1)
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use std::fs;
+    use std::path;
+
+    use crate::radix;
+    use crate::radix::{Base64Config, Encoding};
+
+    fn read_input(name: &str) -> Vec<u8> {
+        fs::read(path::PathBuf::from("./src/set01/input").join(name)).unwrap()
+    }
+
+    #[test]
+    fn test_detect_challenge_files() {
+        let detection = radix::detect_encoding(&read_input("_detect_single_character_xor.txt"));
+
+        assert_eq!(Encoding::Hex, detection.encoding);
+        assert!(detection.confidence > 0.99);
+
+        let detection = radix::detect_encoding(&read_input("_break_repeating_key_xor.txt"));
+
+        assert_eq!(
+            Encoding::Base64 { config: Base64Config::STANDARD, wrapped: true },
+            detection.encoding
+        );
+        assert!(detection.confidence > 0.99);
+    }
+
+    #[test]
+    fn test_detect_encoding() {
+        assert_eq!(Encoding::Hex, radix::detect_encoding(b"49276d206b696c6c696e67\n").encoding);
+        assert_eq!(Encoding::Base32, radix::detect_encoding(b"MZXW6YTBOI======").encoding);
+        assert_eq!(Encoding::Base32, radix::detect_encoding(b"mzxw6ytboi").encoding);
+
+        assert_eq!(
+            Encoding::Base64 { config: Base64Config::STANDARD, wrapped: false },
+            radix::detect_encoding(b"TWFuIE0=\n").encoding
+        );
+
+        assert_eq!(
+            Encoding::Base64 { config: Base64Config::URL_SAFE_NO_PAD, wrapped: false },
+            radix::detect_encoding(b"-_8").encoding
+        );
+
+        assert_eq!(Encoding::Binary, radix::detect_encoding(&[0x00, 0xff, 0x10]).encoding);
+        assert_eq!(Encoding::Binary, radix::detect_encoding(b"Hello, world!").encoding);
+        assert_eq!(Encoding::Binary, radix::detect_encoding(b"").encoding);
+    }
+
+    #[test]
+    fn test_confidence() {
+        // Could just as well be base64
+        let short = radix::detect_encoding(b"cafe");
+        let long = radix::detect_encoding(b"cafebabedeadbeef");
+
+        assert_eq!(Encoding::Hex, short.encoding);
+        assert!(short.confidence < long.confidence);
+
+        assert_eq!(1.0, radix::detect_encoding(&[0x00]).confidence);
+        assert_eq!(0.0, radix::detect_encoding(b"").confidence);
+
+        // Base64 with a bad length vs plain English
+        assert!(
+            radix::detect_encoding(b"TWFuIE").confidence < radix::detect_encoding(b"Hi, you!").confidence
+        );
+    }
+
+    #[test]
+    fn test_decode_auto() {
+        assert_eq!(b"Man M".to_vec(), radix::decode_auto(b"TWFu\nIE0=\n"));
+        assert_eq!(b"foobar".to_vec(), radix::decode_auto(b"MZXW6YTBOI======"));
+        assert_eq!(vec![0xde, 0xad, 0xbe, 0xef], radix::decode_auto(b"dead beef"));
+        assert_eq!(vec![0x00, 0xff], radix::decode_auto(&[0x00, 0xff]));
+
+        let contents = read_input("_break_repeating_key_xor.txt");
+
+        assert_eq!(
+            radix::mime_base64_to_bytes(std::str::from_utf8(&contents).unwrap()),
+            radix::decode_auto(&contents)
+        );
+    }
+}
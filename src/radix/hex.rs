@@ -0,0 +1,292 @@
+//! Parsing and formatting hex the way other tools write it
+//!
+//! `base16_to_bytes`/`bytes_to_base16` only deal with one unbroken run of hex
+//! digits. Out in the wild, hex comes with all sorts of decoration depending
+//! on where it was copied from:
+//!
+//! - Wireshark: `de:ad:be:ef`
+//! - openssl, hexdumps: `de ad be ef`, `dead beef`
+//! - C source: `{0xde, 0xad, 0xbe, 0xef}`
+//!
+//! `parse_hex` in `HexMode::Lenient` takes all of those. `format_hex` writes
+//! them.
+
+use crate::radix;
+use crate::radix::DecodeError;
+
+/// How picky `parse_hex` should be
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HexMode {
+    /// Nothing but an even number of hex digits
+    Strict,
+
+    /// Hex digits broken up by whitespace, `:`, `-`, `,`, braces or brackets,
+    /// each run optionally starting with `0x`.
+    /// A run with an odd number of digits is read as a number, so `0x1` is
+    /// the byte 0x01 and `123` is 0x01 0x23.
+    Lenient,
+}
+
+/// How `format_hex` should write bytes out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HexFormat {
+    pub uppercase: bool,
+
+    /// How many bytes to put together before a separator. 0 means never
+    /// separate.
+    pub group_size: usize,
+
+    /// What goes between groups
+    pub separator: &'static str,
+
+    /// What goes in front of every group
+    pub prefix: &'static str,
+}
+
+impl HexFormat {
+    /// `deadbeef`, like `bytes_to_base16`
+    pub const PLAIN: HexFormat = HexFormat {
+        uppercase: false,
+        group_size: 0,
+        separator: "",
+        prefix: "",
+    };
+
+    /// `de:ad:be:ef`, like Wireshark's "Copy as Hex Stream" with separators
+    pub const COLON: HexFormat = HexFormat {
+        uppercase: false,
+        group_size: 1,
+        separator: ":",
+        prefix: "",
+    };
+
+    /// `de ad be ef`
+    pub const SPACED: HexFormat = HexFormat {
+        uppercase: false,
+        group_size: 1,
+        separator: " ",
+        prefix: "",
+    };
+
+    /// `0xde, 0xad, 0xbe, 0xef`, ready to go between the braces of a C array
+    pub const C_ARRAY: HexFormat = HexFormat {
+        uppercase: false,
+        group_size: 1,
+        separator: ", ",
+        prefix: "0x",
+    };
+}
+
+impl Default for HexFormat {
+    fn default() -> Self {
+        HexFormat::PLAIN
+    }
+}
+
+/// Parse hex written in any of the notations described by `mode`
+///
+/// # Panics
+///
+/// Will panic if `hex_str` can't be parsed. See `try_parse_hex`
+pub fn parse_hex(hex_str: &str, mode: HexMode) -> Vec<u8> {
+    try_parse_hex(hex_str, mode)
+        .unwrap_or_else(|e| panic!("error: invalid hex string - {}", e))
+}
+
+/// Parse hex written in any of the notations described by `mode`, failing
+/// with a `DecodeError` instead of panicking
+pub fn try_parse_hex(hex_str: &str, mode: HexMode) -> Result<Vec<u8>, DecodeError> {
+    let input = hex_str.as_bytes();
+
+    match mode {
+        HexMode::Strict => {
+            // No guessing where a missing digit should go
+            if input.len() % 2 != 0 {
+                return Err(DecodeError::InvalidLength { offset: input.len() });
+            }
+
+            radix::try_base16_to_bytes(hex_str)
+        },
+        HexMode::Lenient => {
+            let mut bytes = Vec::with_capacity(input.len() / 2);
+
+            let mut offset = 0;
+
+            while offset < input.len() {
+                if is_hex_separator(&input[offset]) {
+                    offset += 1;
+                    continue;
+                }
+
+                // The run of digits goes until the next separator
+                let end = input[offset..].iter()
+                                        .position(is_hex_separator)
+                                        .map_or(input.len(), |length| offset + length);
+
+                bytes.append(&mut parse_hex_run(&input[offset..end], offset)?);
+
+                offset = end;
+            }
+
+            Ok(bytes)
+        },
+    }
+}
+
+/// Format bytes as hex, as described by `format`
+pub fn format_hex(bytes: &[u8], format: HexFormat) -> String {
+    let mut hex = String::with_capacity(bytes.len() * 2);
+
+    let group_size = if format.group_size == 0 { bytes.len().max(1) } else { format.group_size };
+
+    for (index, group) in bytes.chunks(group_size).enumerate() {
+        if index > 0 {
+            hex.push_str(format.separator);
+        }
+
+        hex.push_str(format.prefix);
+
+        for byte in group {
+            for tetrad in [byte >> 4, byte & 0b0000_1111].iter() {
+                let base16_char = radix::tetrad_to_base16(tetrad);
+
+                hex.push(if format.uppercase {
+                    base16_char.to_ascii_uppercase()
+                }
+                else {
+                    base16_char
+                } as char);
+            }
+        }
+    }
+
+    hex
+}
+
+/// Parse one run of hex digits between separators. `offset` is where the
+/// run starts in the original input.
+fn parse_hex_run(run: &[u8], offset: usize) -> Result<Vec<u8>, DecodeError> {
+    let (digits, offset) = if run.starts_with(b"0x") || run.starts_with(b"0X") {
+        (&run[2..], offset + 2)
+    }
+    else {
+        (run, offset)
+    };
+
+    // A lone "0x" is not a number
+    if digits.is_empty() {
+        return Err(DecodeError::InvalidLength { offset });
+    }
+
+    // The run was cut out of a `&str` at ascii characters, so it is still
+    // valid utf8
+    let digits = std::str::from_utf8(digits).unwrap();
+
+    // `try_base16_to_bytes` takes care of odd lengths. We just need to move
+    // the offsets in any error over to where the run starts
+    radix::try_base16_to_bytes(digits).map_err(|e| match e {
+        DecodeError::InvalidCharacter { byte, offset: position } => {
+            DecodeError::InvalidCharacter { byte, offset: offset + position }
+        },
+        e => e,
+    })
+}
+
+/// Characters that `HexMode::Lenient` skips over
+fn is_hex_separator(c: &u8) -> bool {
+    c.is_ascii_whitespace() || matches!(c, b':' | b'-' | b',' | b'{' | b'}' | b'[' | b']')
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use crate::radix;
+    use crate::radix::{DecodeError, HexFormat, HexMode};
+
+    #[test]
+    fn test_parse_hex_strict() {
+        assert_eq!(vec![0xde, 0xad, 0xbe, 0xef], radix::parse_hex("DEADbeef", HexMode::Strict));
+
+        assert_eq!(
+            Err(DecodeError::InvalidLength { offset: 3 }),
+            radix::try_parse_hex("123", HexMode::Strict)
+        );
+
+        assert_eq!(
+            Err(DecodeError::InvalidCharacter { byte: b':', offset: 2 }),
+            radix::try_parse_hex("de:ad:", HexMode::Strict)
+        );
+    }
+
+    #[test]
+    fn test_parse_hex_lenient() {
+        let deadbeef = vec![0xde, 0xad, 0xbe, 0xef];
+
+        for notation in [
+            "deadbeef",
+            "0xdeadbeef",
+            "de:ad:be:ef",
+            "DE-AD-BE-EF",
+            "de ad be ef",
+            "dead beef\n",
+            "{0xde, 0xad, 0xbe, 0xef}",
+            "[0xDE,0xAD,0xBE,0xEF]",
+        ].iter() {
+            assert_eq!(deadbeef, radix::parse_hex(notation, HexMode::Lenient), "{}", notation);
+        }
+
+        // Each run is a number on its own
+        assert_eq!(vec![0x01, 0x02, 0x0a], radix::parse_hex("{0x1, 0x2, 0xa}", HexMode::Lenient));
+        assert_eq!(vec![0x01, 0x23], radix::parse_hex("123", HexMode::Lenient));
+        assert_eq!(Vec::<u8>::new(), radix::parse_hex("{}", HexMode::Lenient));
+    }
+
+    #[test]
+    fn test_try_parse_hex_lenient() {
+        assert_eq!(
+            Err(DecodeError::InvalidCharacter { byte: b'g', offset: 9 }),
+            radix::try_parse_hex("{0xde, 0xgd}", HexMode::Lenient)
+        );
+
+        assert_eq!(
+            Err(DecodeError::InvalidLength { offset: 5 }),
+            radix::try_parse_hex("de 0x", HexMode::Lenient)
+        );
+
+        assert_eq!(
+            Err(DecodeError::InvalidCharacter { byte: 0xc3, offset: 2 }),
+            radix::try_parse_hex("de\u{e9}", HexMode::Lenient)
+        );
+    }
+
+    #[test]
+    fn test_format_hex() {
+        let bytes = [0xde, 0xad, 0xbe, 0xef];
+
+        assert_eq!("deadbeef", radix::format_hex(&bytes, HexFormat::PLAIN));
+        assert_eq!("de:ad:be:ef", radix::format_hex(&bytes, HexFormat::COLON));
+        assert_eq!("de ad be ef", radix::format_hex(&bytes, HexFormat::SPACED));
+        assert_eq!("0xde, 0xad, 0xbe, 0xef", radix::format_hex(&bytes, HexFormat::C_ARRAY));
+
+        let format = HexFormat {
+            uppercase: true,
+            group_size: 2,
+            separator: " ",
+            prefix: "",
+        };
+
+        assert_eq!("DEAD BEEF 01", radix::format_hex(&[0xde, 0xad, 0xbe, 0xef, 0x01], format));
+        assert_eq!("", radix::format_hex(&[], format));
+    }
+
+    #[test]
+    fn test_format_then_parse() {
+        let bytes = (0..=255).collect::<Vec<u8>>();
+
+        for format in [HexFormat::PLAIN, HexFormat::COLON, HexFormat::SPACED, HexFormat::C_ARRAY].iter() {
+            assert_eq!(
+                bytes,
+                radix::parse_hex(&radix::format_hex(&bytes, *format), HexMode::Lenient)
+            );
+        }
+    }
+}
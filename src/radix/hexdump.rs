@@ -0,0 +1,207 @@
+//! An xxd-style hexdump that knows about cipher blocks
+//!
+//! Printing cipher text with `bytes_to_base16` gives one long line, and good
+//! luck spotting two identical 16-byte blocks in there. Challenges 8 and 11
+//! are all about those repeated blocks.
+//!
+//! So here, each line is exactly one block, and blocks that show up more
+//! than once in the buffer can be tagged. Every distinct repeated block gets
+//! its own number (`#1`, `#2`...) so you can tell which lines match:
+//!
+//! ```text
+//! 00000000: d1 aa 4f 65 78 92 65 42 fb b6 dd 87 6c d2 05 08  ..Oex.eB....l...  #1
+//! 00000010: d1 aa 4f 65 78 92 65 42 fb b6 dd 87 6c d2 05 08  ..Oex.eB....l...  #1
+//! 00000020: 60 fa 36 70 7e 45 f4 99 db a0 f2 5b 92 23 01 a5  `.6p~E.....[.#..
+//! ```
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// The colours used in coloured mode, one per repeated block (cycling)
+const ANSI_COLORS: [&str; 6] = [
+    "\x1b[31m", "\x1b[32m", "\x1b[33m", "\x1b[34m", "\x1b[35m", "\x1b[36m"
+];
+
+const ANSI_RESET: &str = "\x1b[0m";
+
+/// How `hexdump_with` should lay things out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HexdumpConfig {
+    /// How many bytes go on each line. This is also what counts as a block
+    /// when looking for repeats.
+    pub block_size: usize,
+
+    /// Tag blocks that show up more than once
+    pub mark_repeats: bool,
+
+    /// Colour the repeated blocks with ANSI escape codes. Only makes a
+    /// difference together with `mark_repeats`.
+    pub color: bool,
+}
+
+impl Default for HexdumpConfig {
+    /// One AES block per line, with repeated blocks tagged and no colour
+    fn default() -> Self {
+        HexdumpConfig {
+            block_size: 16,
+            mark_repeats: true,
+            color: false,
+        }
+    }
+}
+
+/// Hexdump `bytes` with the default `HexdumpConfig`
+pub fn hexdump(bytes: &[u8]) -> String {
+    hexdump_with(bytes, HexdumpConfig::default())
+}
+
+/// Hexdump `bytes`: an offset, the bytes in base16 and their ascii
+/// characters on each line, one block per line
+///
+/// # Panics
+///
+/// Will panic if `config.block_size` is 0
+pub fn hexdump_with(bytes: &[u8], config: HexdumpConfig) -> String {
+    assert!(config.block_size > 0, "error: the block size must be greater than zero");
+
+    let block_size = config.block_size;
+
+    let labels = if config.mark_repeats {
+        label_repeated_blocks(bytes, block_size)
+    }
+    else {
+        HashMap::new()
+    };
+
+    let mut dump = String::new();
+
+    for (index, block) in bytes.chunks(block_size).enumerate() {
+        let label = labels.get(block).cloned();
+
+        let (start_color, end_color) = match label {
+            Some(label) if config.color => (ANSI_COLORS[(label - 1) % ANSI_COLORS.len()], ANSI_RESET),
+            _ => ("", ""),
+        };
+
+        // Writing to a `String` can't fail, hence the unwraps
+        write!(dump, "{:08x}: {}", index * block_size, start_color).unwrap();
+
+        for (i, byte) in block.iter().enumerate() {
+            if i > 0 {
+                dump.push(' ');
+            }
+
+            write!(dump, "{:02x}", byte).unwrap();
+        }
+
+        // Line the ascii column up on a short last block
+        for _ in block.len()..block_size {
+            dump.push_str("   ");
+        }
+
+        dump.push_str(end_color);
+        dump.push_str("  ");
+        dump.push_str(start_color);
+
+        for byte in block {
+            dump.push(if byte.is_ascii_graphic() || *byte == b' ' { *byte as char } else { '.' });
+        }
+
+        dump.push_str(end_color);
+
+        if let Some(label) = label {
+            write!(dump, "  #{}", label).unwrap();
+        }
+
+        dump.push('\n');
+    }
+
+    dump
+}
+
+/// Number every distinct block that shows up more than once, in the order
+/// they first show up. Only whole blocks count.
+fn label_repeated_blocks(bytes: &[u8], block_size: usize) -> HashMap<&[u8], usize> {
+    let mut counts: HashMap<&[u8], usize> = HashMap::new();
+
+    for block in bytes.chunks_exact(block_size) {
+        *counts.entry(block).or_insert(0) += 1;
+    }
+
+    let mut labels = HashMap::new();
+
+    for block in bytes.chunks_exact(block_size) {
+        if counts[block] > 1 && !labels.contains_key(block) {
+            let label = labels.len() + 1;
+            labels.insert(block, label);
+        }
+    }
+
+    labels
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use crate::aes128;
+    use crate::radix;
+    use crate::radix::HexdumpConfig;
+
+    #[test]
+    fn test_hexdump() {
+        let cipher_text = aes128::ecb_encrypt(
+            &aes128::Key::from_bytes(*b"YELLOW SUBMARINE"),
+            b"YELLOW SUBMARINEYELLOW SUBMARINE"
+        );
+
+        assert_eq!(
+            "00000000: d1 aa 4f 65 78 92 65 42 fb b6 dd 87 6c d2 05 08  ..Oex.eB....l...  #1\n\
+             00000010: d1 aa 4f 65 78 92 65 42 fb b6 dd 87 6c d2 05 08  ..Oex.eB....l...  #1\n\
+             00000020: 60 fa 36 70 7e 45 f4 99 db a0 f2 5b 92 23 01 a5  `.6p~E.....[.#..\n",
+            radix::hexdump(&cipher_text)
+        );
+    }
+
+    #[test]
+    fn test_hexdump_with() {
+        let config = HexdumpConfig {
+            block_size: 4,
+            mark_repeats: true,
+            color: false,
+        };
+
+        assert_eq!(
+            "00000000: 41 41 41 41  AAAA  #1\n\
+             00000004: 42 42 42 42  BBBB  #2\n\
+             00000008: 41 41 41 41  AAAA  #1\n\
+             0000000c: 42 42 42 42  BBBB  #2\n\
+             00000010: 43 0a        C.\n",
+            radix::hexdump_with(b"AAAABBBBAAAABBBBC\n", config)
+        );
+
+        let config = HexdumpConfig { mark_repeats: false, ..config };
+
+        assert_eq!(
+            "00000000: 41 41 41 41  AAAA\n\
+             00000004: 41 41 41 41  AAAA\n",
+            radix::hexdump_with(b"AAAAAAAA", config)
+        );
+
+        assert_eq!("", radix::hexdump_with(b"", config));
+    }
+
+    #[test]
+    fn test_hexdump_with_color() {
+        let config = HexdumpConfig {
+            block_size: 2,
+            mark_repeats: true,
+            color: true,
+        };
+
+        assert_eq!(
+            "00000000: \x1b[31m41 41\x1b[0m  \x1b[31mAA\x1b[0m  #1\n\
+             00000002: 42 43  BC\n\
+             00000004: \x1b[31m41 41\x1b[0m  \x1b[31mAA\x1b[0m  #1\n",
+            radix::hexdump_with(b"AABCAA", config)
+        );
+    }
+}
@@ -0,0 +1,1120 @@
+//! Radix Conversions
+//! 
+//! After getting to a point in the cryptopals challenges, one realizes these 
+//! are used very frequently
+//! So I have gathered them all in this here module for ease of use/organization
+
+use std::error;
+use std::fmt;
+
+pub mod stream;
+
+mod ascii85;
+mod base32;
+mod base58;
+mod detect;
+mod hex;
+mod hexdump;
+
+pub use self::ascii85::{bytes_to_ascii85, ascii85_to_bytes, try_ascii85_to_bytes};
+pub use self::base32::{bytes_to_base32, base32_to_bytes, try_base32_to_bytes};
+pub use self::base58::{bytes_to_base58, base58_to_bytes, try_base58_to_bytes};
+pub use self::detect::{detect_encoding, decode_auto, Encoding, Detection};
+pub use self::hex::{parse_hex, try_parse_hex, format_hex, HexMode, HexFormat};
+pub use self::hexdump::{hexdump, hexdump_with, HexdumpConfig};
+
+pub const START_ASCII_DIGIT: u8 = 48;
+pub const END_ASCII_DIGIT: u8 = START_ASCII_DIGIT + 9;
+pub const START_ASCII_UPALPHA: u8 = 65;
+pub const END_ASCII_UPALPHA: u8 = START_ASCII_UPALPHA + 25;
+pub const START_ASCII_LOALPHA: u8 = 97;
+pub const END_ASCII_LOALPHA: u8 = START_ASCII_LOALPHA + 25;
+
+const START_BASE64_UPALPHA: u8 = 0;
+const START_BASE64_LOALPHA: u8 = 26;
+const START_BASE64_DIGIT: u8 = 52;
+
+/// What the decoding tables hold for bytes that are not part of the alphabet
+const INVALID: u8 = 0xff;
+
+// Lookup tables for the hot loops. Matching on ranges of characters one at a
+// time is easy to read, but a single index into a table is a lot faster.
+// The tables are still built from the same ranges, at compile time.
+static BASE16_ENCODE: [u8; 16] = base16_encode_table();
+static BASE16_DECODE: [u8; 256] = base16_decode_table();
+static BASE64_STANDARD_ENCODE: [u8; 64] = base64_encode_table(b'+', b'/');
+static BASE64_STANDARD_DECODE: [u8; 256] = decode_table(&base64_encode_table(b'+', b'/'));
+static BASE64_URL_SAFE_ENCODE: [u8; 64] = base64_encode_table(b'-', b'_');
+static BASE64_URL_SAFE_DECODE: [u8; 256] = decode_table(&base64_encode_table(b'-', b'_'));
+
+/// The reasons decoding a string with any of the encodings here can fail
+///
+/// Every variant carries the byte offset into the input where the problem was
+/// found, so that a bad line in a file can actually be tracked down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The byte at `offset` is not part of the alphabet
+    InvalidCharacter { byte: u8, offset: usize },
+
+    /// The input length is not valid for the encoding. `offset` is the length
+    /// of the input
+    InvalidLength { offset: usize },
+
+    /// A padding character showed up somewhere other than the end of the input
+    InvalidPadding { offset: usize },
+
+    /// The character at `offset` has bits set that would be thrown away when
+    /// decoding. A proper encoder would never have produced it.
+    NonCanonicalTrailingBits { offset: usize },
+
+    /// The group of characters starting at `offset` stands for a number that
+    /// does not fit in the bytes it is supposed to decode to (Ascii85)
+    Overflow { offset: usize },
+}
+
+impl DecodeError {
+    /// The byte offset into the input where decoding failed
+    pub fn offset(&self) -> usize {
+        match *self {
+            DecodeError::InvalidCharacter { offset, .. } => offset,
+            DecodeError::InvalidLength { offset } => offset,
+            DecodeError::InvalidPadding { offset } => offset,
+            DecodeError::NonCanonicalTrailingBits { offset } => offset,
+            DecodeError::Overflow { offset } => offset,
+        }
+    }
+
+    /// The same error, with `offset` moved to `f(offset)`
+    ///
+    /// For when the input was decoded after being cut out of something bigger
+    pub fn map_offset<F: FnOnce(usize) -> usize>(self, f: F) -> DecodeError {
+        match self {
+            DecodeError::InvalidCharacter { byte, offset } => {
+                DecodeError::InvalidCharacter { byte, offset: f(offset) }
+            },
+            DecodeError::InvalidLength { offset } => DecodeError::InvalidLength { offset: f(offset) },
+            DecodeError::InvalidPadding { offset } => DecodeError::InvalidPadding { offset: f(offset) },
+            DecodeError::NonCanonicalTrailingBits { offset } => {
+                DecodeError::NonCanonicalTrailingBits { offset: f(offset) }
+            },
+            DecodeError::Overflow { offset } => DecodeError::Overflow { offset: f(offset) },
+        }
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            DecodeError::InvalidCharacter { byte, offset } => {
+                write!(f, "invalid character {:#04x} at offset {}", byte, offset)
+            },
+            DecodeError::InvalidLength { offset } => {
+                write!(f, "invalid length {}", offset)
+            },
+            DecodeError::InvalidPadding { offset } => {
+                write!(f, "misplaced padding at offset {}", offset)
+            },
+            DecodeError::NonCanonicalTrailingBits { offset } => {
+                write!(f, "non-canonical trailing bits at offset {}", offset)
+            },
+            DecodeError::Overflow { offset } => {
+                write!(f, "group at offset {} is out of range", offset)
+            },
+        }
+    }
+}
+
+impl error::Error for DecodeError {}
+
+/// The two base64 alphabets from RFC 4648
+///
+/// They only differ in the characters used for 62 and 63.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Base64Alphabet {
+    /// `+` and `/` (RFC 4648 §4)
+    Standard,
+
+    /// `-` and `_`, which can go in URLs and file names (RFC 4648 §5)
+    UrlSafe,
+}
+
+impl Base64Alphabet {
+    /// Sextet -> character
+    fn encode_table(self) -> &'static [u8; 64] {
+        match self {
+            Base64Alphabet::Standard => &BASE64_STANDARD_ENCODE,
+            Base64Alphabet::UrlSafe => &BASE64_URL_SAFE_ENCODE,
+        }
+    }
+
+    /// Character -> sextet, or `INVALID`
+    fn decode_table(self) -> &'static [u8; 256] {
+        match self {
+            Base64Alphabet::Standard => &BASE64_STANDARD_DECODE,
+            Base64Alphabet::UrlSafe => &BASE64_URL_SAFE_DECODE,
+        }
+    }
+}
+
+/// How base64 should be encoded/decoded
+///
+/// When `padding` is `true`, the encoder emits trailing `=` and the decoder
+/// insists on them. When it is `false`, the encoder leaves them out and the 
+/// decoder rejects them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Base64Config {
+    pub alphabet: Base64Alphabet,
+    pub padding: bool,
+}
+
+impl Base64Config {
+    /// `+` and `/`, padded with `=`. What `bytes_to_base64`/`base64_to_bytes`
+    /// use
+    pub const STANDARD: Base64Config = Base64Config {
+        alphabet: Base64Alphabet::Standard,
+        padding: true,
+    };
+
+    /// `+` and `/`, without the trailing `=`
+    pub const STANDARD_NO_PAD: Base64Config = Base64Config {
+        alphabet: Base64Alphabet::Standard,
+        padding: false,
+    };
+
+    /// `-` and `_`, padded with `=`
+    pub const URL_SAFE: Base64Config = Base64Config {
+        alphabet: Base64Alphabet::UrlSafe,
+        padding: true,
+    };
+
+    /// `-` and `_`, without the trailing `=`. What cookies, JWTs and query
+    /// parameters usually look like
+    pub const URL_SAFE_NO_PAD: Base64Config = Base64Config {
+        alphabet: Base64Alphabet::UrlSafe,
+        padding: false,
+    };
+}
+
+impl Default for Base64Config {
+    fn default() -> Self {
+        Base64Config::STANDARD
+    }
+}
+
+/// Convert a base16 to the sequence of binary numbers it represents
+///
+/// An odd number of digits is taken to be missing a leading 0. For `0x`
+/// prefixes, separators, or to reject odd lengths instead, see `parse_hex`.
+///
+/// # Panics
+///
+/// Will panic if `base16_str` is not valid base16. See `try_base16_to_bytes`
+pub fn base16_to_bytes(base16_str: &str) -> Vec<u8> {
+    try_base16_to_bytes(base16_str)
+        .unwrap_or_else(|e| panic!("error: invalid base16 string - {}", e))
+}
+
+/// Convert a base16 to the sequence of binary numbers it represents, failing
+/// with a `DecodeError` instead of panicking
+pub fn try_base16_to_bytes(base16_str: &str) -> Result<Vec<u8>, DecodeError> {
+    let input = base16_str.as_bytes();
+
+    let mut bytes = vec![0; base16_decoded_len(input.len())];
+
+    try_base16_to_bytes_into(input, &mut bytes)?;
+
+    Ok(bytes)
+}
+
+/// How many bytes `len` base16 characters decode to
+pub fn base16_decoded_len(len: usize) -> usize {
+    len.div_ceil(2)
+}
+
+/// Convert base16 to the sequence of binary numbers it represents, writing
+/// them to the start of `out` instead of allocating
+///
+/// Returns how many bytes were written. On error, `out` may have been
+/// partially written to.
+///
+/// # Panics
+///
+/// Will panic if `out` is shorter than `base16_decoded_len(input.len())`
+pub fn try_base16_to_bytes_into(input: &[u8], out: &mut [u8]) -> Result<usize, DecodeError> {
+    let needed = base16_decoded_len(input.len());
+
+    assert!(out.len() >= needed, "error: `out` is too short. Need {} bytes, got {}", needed, out.len());
+
+    // If the base16 string is of an un-even length,
+    // there is a leading 0 we should add
+    // e.g. 0x123 is the same as 0x0123
+    // Instead of copying the string to insert it, the first digit becomes a
+    // byte on its own. That way error offsets still point into `input`
+    let shift = input.len() % 2;
+
+    if shift == 1 {
+        out[0] = base16_to_tetrad(&input[0], 0)?;
+    }
+
+    for (index, (pair, byte)) in input[shift..].chunks_exact(2).zip(out[shift..].iter_mut()).enumerate() {
+        let high = BASE16_DECODE[pair[0] as usize];
+        let low = BASE16_DECODE[pair[1] as usize];
+
+        // A tetrad has its top four bits clear. `INVALID` has them all set
+        if (high | low) & 0b1111_0000 != 0 {
+            let offset = shift + index * 2;
+
+            base16_to_tetrad(&pair[0], offset)?;
+            base16_to_tetrad(&pair[1], offset + 1)?;
+        }
+
+        // Smash up each two consecutive tetrads into one byte
+        *byte = high << 4 | low;
+    }
+
+    Ok(needed)
+}
+
+/// Convert a base64 string to the sequence of binary numbers it represents 
+///
+/// # Panics
+///
+/// Will panic if `base64_str` is not valid base64. See `try_base64_to_bytes`
+pub fn base64_to_bytes(base64_str: &str) -> Vec<u8> {
+    try_base64_to_bytes(base64_str)
+        .unwrap_or_else(|e| panic!("error: invalid base64 string - {}", e))
+}
+
+/// Convert a base64 string to the sequence of binary numbers it represents,
+/// failing with a `DecodeError` instead of panicking
+pub fn try_base64_to_bytes(base64_str: &str) -> Result<Vec<u8>, DecodeError> {
+    try_base64_to_bytes_with(base64_str, Base64Config::STANDARD)
+}
+
+/// Convert a base64 string, encoded as described by `config`, to the sequence
+/// of binary numbers it represents
+///
+/// # Panics
+///
+/// Will panic if `base64_str` is not valid base64. See 
+/// `try_base64_to_bytes_with`
+pub fn base64_to_bytes_with(base64_str: &str, config: Base64Config) -> Vec<u8> {
+    try_base64_to_bytes_with(base64_str, config)
+        .unwrap_or_else(|e| panic!("error: invalid base64 string - {}", e))
+}
+
+/// Convert a base64 string, encoded as described by `config`, to the sequence
+/// of binary numbers it represents, failing with a `DecodeError` instead of 
+/// panicking
+pub fn try_base64_to_bytes_with(
+    base64_str: &str,
+    config: Base64Config)
+    -> Result<Vec<u8>, DecodeError>
+{
+    let input = base64_str.as_bytes();
+
+    let mut bytes = vec![0; max_base64_decoded_len(input.len())];
+
+    let written = try_base64_to_bytes_into(input, config, &mut bytes)?;
+    bytes.truncate(written);
+
+    Ok(bytes)
+}
+
+/// Convert a MIME (RFC 2045) base64 string to the sequence of binary numbers
+/// it represents
+///
+/// # Panics
+///
+/// Will panic if `base64_str` is not valid base64. See 
+/// `try_mime_base64_to_bytes`
+pub fn mime_base64_to_bytes(base64_str: &str) -> Vec<u8> {
+    try_mime_base64_to_bytes(base64_str)
+        .unwrap_or_else(|e| panic!("error: invalid base64 string - {}", e))
+}
+
+/// Convert a MIME (RFC 2045) base64 string to the sequence of binary numbers
+/// it represents, failing with a `DecodeError` instead of panicking
+///
+/// MIME wraps base64 into lines, so this skips over any whitespace and line
+/// breaks (`\n` or `\r\n`) before decoding. This is what the challenge
+/// input files look like.  
+/// Error offsets still point into the original `base64_str`.
+pub fn try_mime_base64_to_bytes(base64_str: &str) -> Result<Vec<u8>, DecodeError> {
+    let input = base64_str.as_bytes();
+
+    let significant = without_base64_whitespace(input);
+
+    let mut bytes = vec![0; max_base64_decoded_len(significant.len())];
+
+    let written = try_base64_to_bytes_into(&significant, Base64Config::STANDARD, &mut bytes)
+        .map_err(|e| e.map_offset(|index| significant_offset(input, index)))?;
+
+    bytes.truncate(written);
+
+    Ok(bytes)
+}
+
+/// The most bytes `len` base64 characters can decode to
+///
+/// This is exact for unpadded base64. With padding, it can be up to 2 bytes
+/// more than what actually comes out, since the padding is counted as data.
+pub fn max_base64_decoded_len(len: usize) -> usize {
+    len / 4 * 3 + (len % 4).saturating_sub(1)
+}
+
+/// Convert base64, encoded as described by `config`, to the sequence of 
+/// binary numbers it represents, writing them to the start of `out` instead
+/// of allocating
+///
+/// Returns how many bytes were written. On error, `out` may have been
+/// partially written to.
+///
+/// # Panics
+///
+/// Will panic if `out` is too short for the decoded bytes. 
+/// `max_base64_decoded_len(input.len())` is always enough.
+pub fn try_base64_to_bytes_into(
+    input: &[u8],
+    config: Base64Config,
+    out: &mut [u8])
+    -> Result<usize, DecodeError>
+{
+    let table = config.alphabet.decode_table();
+
+    // Work out where the data stops and the padding starts up front, so that
+    // the loop below only ever sees whole quanta of data
+    let data_len = if config.padding {
+        // Base64 strings seem to always be in multiples of 4 sextets because of padding. Nice
+        // We can use that to make the conversion easier
+        if input.len() % 4 != 0 {
+            return Err(DecodeError::InvalidLength { offset: input.len() });
+        }
+
+        input.len() - input.iter().rev().take(2).take_while(|c| **c == b'=').count()
+    }
+    else {
+        // A single leftover sextet is not even enough for one byte
+        if input.len() % 4 == 1 {
+            // ...unless the real problem is that there is padding
+            return Err(match input.iter().position(|c| *c == b'=') {
+                Some(offset) => DecodeError::InvalidPadding { offset },
+                None => DecodeError::InvalidLength { offset: input.len() },
+            });
+        }
+
+        input.len()
+    };
+
+    let (quanta, tail) = input[..data_len].split_at(data_len - data_len % 4);
+
+    let needed = max_base64_decoded_len(data_len);
+
+    assert!(out.len() >= needed, "error: `out` is too short. Need {} bytes, got {}", needed, out.len());
+
+    for (index, (quantum, bytes)) in quanta.chunks_exact(4).zip(out.chunks_exact_mut(3)).enumerate() {
+        let first = table[quantum[0] as usize];
+        let second = table[quantum[1] as usize];
+        let third = table[quantum[2] as usize];
+        let fourth = table[quantum[3] as usize];
+
+        // A sextet has its top two bits clear. `INVALID` has them all set
+        if (first | second | third | fourth) & 0b1100_0000 != 0 {
+            for (i, base64_char) in quantum.iter().enumerate() {
+                base64_to_sextet(base64_char, index * 4 + i, config.alphabet)?;
+            }
+        }
+
+        // no padding -> we are decoding into 3 bytes
+        bytes[0] = (first << 2) | (second >> 4);
+        bytes[1] = (second << 4) | (third >> 2);
+        bytes[2] = (third << 6) | fourth;
+    }
+
+    let offset = quanta.len();
+    let written = quanta.len() / 4 * 3;
+
+    match *tail {
+        [] => {},
+        // 2 padding characters -> we are decoding into 1 bytes
+        // The bottom four bits of the second sextet do not make it into the
+        // output, so they had better be zero
+        [first, second] => {
+            let first = base64_to_sextet(&first, offset, config.alphabet)?;
+            let second = base64_to_sextet(&second, offset + 1, config.alphabet)?;
+
+            if second & 0b0000_1111 != 0 {
+                return Err(DecodeError::NonCanonicalTrailingBits { offset: offset + 1 });
+            }
+
+            out[written] = (first << 2) | (second >> 4);
+        },
+        // 1 padding character -> we are decoding into 2 bytes
+        [first, second, third] => {
+            let first = base64_to_sextet(&first, offset, config.alphabet)?;
+            let second = base64_to_sextet(&second, offset + 1, config.alphabet)?;
+            let third = base64_to_sextet(&third, offset + 2, config.alphabet)?;
+
+            if third & 0b0000_0011 != 0 {
+                return Err(DecodeError::NonCanonicalTrailingBits { offset: offset + 2 });
+            }
+
+            out[written] = (first << 2) | (second >> 4);
+            out[written + 1] = (second << 4) | (third >> 2);
+        },
+        // The length checks above rule out a single leftover character
+        _ => unreachable!(),
+    }
+
+    Ok(needed)
+}
+
+/// Convert a sequence of binary numbers to a Base16 representation
+///
+/// Always lowercase, without separators. See `format_hex` for other layouts.
+pub fn bytes_to_base16(bytes: &[u8]) -> String {
+    let mut base16_ascii_characters = vec![0; base16_encoded_len(bytes.len())];
+
+    bytes_to_base16_into(bytes, &mut base16_ascii_characters);
+
+    // If the `unwrap` panics, that would be programmer (me) error
+    String::from_utf8(base16_ascii_characters).unwrap()
+}
+
+/// How many base16 characters `len` bytes encode to
+pub fn base16_encoded_len(len: usize) -> usize {
+    len * 2
+}
+
+/// Convert a sequence of binary numbers to a Base16 representation, writing
+/// it to the start of `out` instead of allocating
+///
+/// Returns how many characters were written.
+///
+/// # Panics
+///
+/// Will panic if `out` is shorter than `base16_encoded_len(bytes.len())`
+pub fn bytes_to_base16_into(bytes: &[u8], out: &mut [u8]) -> usize {
+    let needed = base16_encoded_len(bytes.len());
+
+    assert!(out.len() >= needed, "error: `out` is too short. Need {} bytes, got {}", needed, out.len());
+
+    // For each byte, we seperate into two tetrads (a tetrad is 4 bits the same way an octet is 8 bits)
+    // We convert each tetrad into its ascii hexadecimal character
+    for (byte, base16_chars) in bytes.iter().zip(out.chunks_exact_mut(2)) {
+        base16_chars[0] = BASE16_ENCODE[(byte >> 4) as usize];
+        base16_chars[1] = BASE16_ENCODE[(byte & 0b0000_1111) as usize];
+    }
+
+    needed
+}
+
+/// Convert a sequence of binary numbers to a Base64 representation
+pub fn bytes_to_base64(bytes: &[u8]) -> String {
+    bytes_to_base64_with(bytes, Base64Config::STANDARD)
+}
+
+/// Convert a sequence of binary numbers to a Base64 representation, encoded
+/// as described by `config`
+pub fn bytes_to_base64_with(bytes: &[u8], config: Base64Config) -> String {
+    let mut base64_ascii_characters = vec![0; base64_encoded_len(bytes.len(), config)];
+
+    bytes_to_base64_into(bytes, config, &mut base64_ascii_characters);
+
+    // If the `unwrap` panics, that would be programmer (me) error
+    String::from_utf8(base64_ascii_characters).unwrap()
+}
+
+/// How many base64 characters `len` bytes encode to with `config`
+pub fn base64_encoded_len(len: usize, config: Base64Config) -> usize {
+    if config.padding {
+        len.div_ceil(3) * 4
+    }
+    else {
+        (len * 4).div_ceil(3)
+    }
+}
+
+/// Convert a sequence of binary numbers to a Base64 representation, encoded
+/// as described by `config`, writing it to the start of `out` instead of 
+/// allocating
+///
+/// Returns how many characters were written.
+///
+/// # Panics
+///
+/// Will panic if `out` is shorter than `base64_encoded_len(bytes.len(), config)`
+pub fn bytes_to_base64_into(bytes: &[u8], config: Base64Config, out: &mut [u8]) -> usize {
+    let needed = base64_encoded_len(bytes.len(), config);
+
+    assert!(out.len() >= needed, "error: `out` is too short. Need {} bytes, got {}", needed, out.len());
+
+    let table = config.alphabet.encode_table();
+
+    let groups = bytes.chunks_exact(3);
+    let remainder = groups.remainder();
+
+    for (group, base64_chars) in groups.zip(out.chunks_exact_mut(4)) {
+        // bit manipulation to extract 4 6-bit numbers
+        // out of 3 8-bit numbers
+        base64_chars[0] = table[(group[0] >> 2) as usize];
+        base64_chars[1] = table[(((group[0] & 0b0000_0011) << 4) | (group[1] >> 4)) as usize];
+        base64_chars[2] = table[(((group[1] & 0b0000_1111) << 2) | (group[2] >> 6)) as usize];
+        base64_chars[3] = table[(group[2] & 0b0011_1111) as usize];
+    }
+
+    let written = bytes.len() / 3 * 4;
+    let alphabet = config.alphabet;
+
+    let tail: &[u8] = match *remainder {
+        [] => &[],
+        // bit manipulation to extract 2 6-bit numbers
+        // out of 1 8-bit numbers (with padding)
+        [first] => &[
+            sextet_to_base64(&(first >> 2), alphabet),
+            sextet_to_base64(&((first & 0b0000_0011) << 4), alphabet),
+            b'=',
+            b'=',
+        ],
+        // bit manipulation to extract 3 6-bit numbers
+        // out of 2 8-bit numbers (with padding)
+        [first, second] => &[
+            sextet_to_base64(&(first >> 2), alphabet),
+            sextet_to_base64(&(((first & 0b0000_0011) << 4) | (second >> 4)), alphabet),
+            sextet_to_base64(&((second & 0b0000_1111) << 2), alphabet),
+            b'=',
+        ],
+        _ => unreachable!(),
+    };
+
+    // Without padding, `needed` already leaves the `=` out
+    out[written..needed].copy_from_slice(&tail[..needed - written]);
+
+    needed
+}
+
+/// Convert a sequence of binary numbers to a Base64 representation, broken up
+/// into lines of at most `line_width` characters
+///
+/// Every line, including the last one, ends with a `\n`. This is the layout 
+/// of the challenge input files (60 columns) and of `base64 -w`. MIME uses 76
+/// columns and PEM uses 64.  
+/// A `line_width` of 0 disables wrapping altogether.
+pub fn bytes_to_base64_wrapped(bytes: &[u8], line_width: usize) -> String {
+    let encoded = bytes_to_base64(bytes);
+
+    if line_width == 0 || encoded.is_empty() {
+        return encoded;
+    }
+
+    let mut wrapped = String::with_capacity(
+        encoded.len() + encoded.len().div_ceil(line_width)
+    );
+
+    // `encoded` is all ascii, so these chunks are valid utf8
+    for line in encoded.as_bytes().chunks(line_width) {
+        wrapped.push_str(std::str::from_utf8(line).unwrap());
+        wrapped.push('\n');
+    }
+
+    wrapped
+}
+
+/// Whitespace that is allowed between the characters of MIME base64
+fn is_base64_whitespace(base64_char: &u8) -> bool {
+    matches!(base64_char, b' ' | b'\t' | b'\r' | b'\n')
+}
+
+/// `input` with the whitespace taken out
+fn without_base64_whitespace(input: &[u8]) -> Vec<u8> {
+    input.iter()
+            .filter(|c| !is_base64_whitespace(c))
+            .copied()
+            .collect()
+}
+
+/// Where the `index`th non-whitespace character is in `input`. One past the
+/// last one is the length of `input`
+fn significant_offset(input: &[u8], index: usize) -> usize {
+    input.iter()
+            .enumerate()
+            .filter(|(_, c)| !is_base64_whitespace(c))
+            .nth(index)
+            .map_or(input.len(), |(offset, _)| offset)
+}
+
+/// Convert a single base16 character into the tetrad (4-bits) number it stands for
+/// 
+/// `offset` is the position of the character in the original input, and is 
+/// only used for reporting errors
+fn base16_to_tetrad(base16_char: &u8, offset: usize) -> Result<u8, DecodeError> {
+    match BASE16_DECODE[*base16_char as usize] {
+        // Huh??
+        INVALID => Err(DecodeError::InvalidCharacter { byte: *base16_char, offset }),
+        tetrad => Ok(tetrad),
+    }
+}
+
+/// Convert a tetrad (4-bits)  number into the single hexadecimal character
+/// 
+/// # Panics
+/// 
+/// Will panic if `num` is more than 4 bits
+fn tetrad_to_base16(num: &u8) -> u8 {
+    match BASE16_ENCODE.get(*num as usize) {
+        Some(base16_char) => *base16_char,
+        None => panic!("error: `num` is not a 4-bit number: {}", num),
+    }
+}
+
+/// Converts a single base64 character into the sextet (6-bits) number it stands for
+///
+/// The padding character (i.e. "=") does not actually represent anything. It
+/// is only allowed at the very end, which is for the caller to check, so here
+/// it is always an `InvalidPadding` error.
+/// 
+/// `offset` is the position of the character in the original input, and is 
+/// only used for reporting errors
+fn base64_to_sextet(
+    base64_char: &u8,
+    offset: usize,
+    alphabet: Base64Alphabet)
+    -> Result<u8, DecodeError>
+{
+    match alphabet.decode_table()[*base64_char as usize] {
+        INVALID if *base64_char == b'=' => Err(DecodeError::InvalidPadding { offset }),
+        INVALID => Err(DecodeError::InvalidCharacter { byte: *base64_char, offset }),
+        sextet => Ok(sextet),
+    }
+}
+
+/// Convert a number to its base64 representation  
+/// Due to how the base64 system works, the number can only be 6-bits long  
+/// The remaining two bits on the left must be set to zero.    
+/// 
+/// # Panics
+/// 
+/// Will panic if `num` uses more than 6 bits
+fn sextet_to_base64(num: &u8, alphabet: Base64Alphabet) -> u8 {
+    match alphabet.encode_table().get(*num as usize) {
+        Some(base64_char) => *base64_char,
+        None => panic!("error: `num` is not a 6-bit number: {}", num),
+    }
+}
+
+/// Build the tetrad -> character table
+///
+/// Here, we could have manually listed every single ascii hex character.
+/// An alternative is to use the fact that the ascii represetation of text
+/// encodes the digit characters and alphabet characters in sequence.
+const fn base16_encode_table() -> [u8; 16] {
+    let mut table = [0; 16];
+
+    let mut num = 0;
+    while num < 16 {
+        table[num as usize] = if num < 10 {
+            START_ASCII_DIGIT + num
+        }
+        else {
+            START_ASCII_LOALPHA + num - 10
+        };
+
+        num += 1;
+    }
+
+    table
+}
+
+/// Build the character -> tetrad table. Both cases are accepted
+const fn base16_decode_table() -> [u8; 256] {
+    let mut table = decode_table(&base16_encode_table());
+
+    let mut num = 10;
+    while num < 16 {
+        table[(START_ASCII_UPALPHA + num - 10) as usize] = num;
+        num += 1;
+    }
+
+    table
+}
+
+/// Build the sextet -> character table, given the characters used for the
+/// sextets 62 and 63
+const fn base64_encode_table(sixty_two: u8, sixty_three: u8) -> [u8; 64] {
+    let mut table = [0; 64];
+
+    let mut num = 0;
+    while num < 26 {
+        table[(START_BASE64_UPALPHA + num) as usize] = START_ASCII_UPALPHA + num;
+        table[(START_BASE64_LOALPHA + num) as usize] = START_ASCII_LOALPHA + num;
+
+        if num < 10 {
+            table[(START_BASE64_DIGIT + num) as usize] = START_ASCII_DIGIT + num;
+        }
+
+        num += 1;
+    }
+
+    table[62] = sixty_two; // Plus sign or dash
+    table[63] = sixty_three; // Forward slash or underscore
+
+    table
+}
+
+/// Turn a number -> character table around. Anything that isn't in it maps
+/// to `INVALID`
+const fn decode_table(encode_table: &[u8]) -> [u8; 256] {
+    let mut table = [INVALID; 256];
+
+    let mut num = 0;
+    while num < encode_table.len() {
+        table[encode_table[num] as usize] = num as u8;
+        num += 1;
+    }
+
+    table
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use std::fs;
+    use std::path;
+
+    use crate::radix;
+
+    #[test]
+    fn test_base16_to_bytes()
+    {
+        assert_eq!(vec![0x01,0x23,0x45,0x67,0x89], radix::base16_to_bytes("0123456789"));
+        assert_eq!(vec![0xab,0xcd,0xef], radix::base16_to_bytes("abcdef"));
+        assert_eq!(vec![0x01, 0x23], radix::base16_to_bytes("123"));
+    }
+
+    /// Test! Test! Test
+    /// I did not test this method enough and it cost me days of trying to track down what was going wrong
+    /// with the decryption upstream
+    #[test]
+    fn test_base64_to_bytes() {
+        assert_eq!(b"Man".to_vec(), radix::base64_to_bytes("TWFu"));
+        assert_eq!(b"Ma".to_vec(), radix::base64_to_bytes("TWE="));
+        assert_eq!(b"M".to_vec(), radix::base64_to_bytes("TQ=="));
+    
+        assert_eq!(
+            b"Without padding".to_vec(),
+            radix::base64_to_bytes("V2l0aG91dCBwYWRkaW5n")
+        );
+        
+        assert_eq!(
+            b"With one padding!".to_vec(), 
+            radix::base64_to_bytes("V2l0aCBvbmUgcGFkZGluZyE=")
+        );
+
+        assert_eq!(
+            b"With two paddings padding!!!".to_vec(),
+            radix::base64_to_bytes("V2l0aCB0d28gcGFkZGluZ3MgcGFkZGluZyEhIQ==")
+        );
+    }
+
+    #[test]
+    fn test_try_base16_to_bytes() {
+        assert_eq!(Ok(vec![0xde, 0xad, 0xbe, 0xef]), radix::try_base16_to_bytes("deadbeef"));
+        assert_eq!(Ok(vec![]), radix::try_base16_to_bytes(""));
+
+        assert_eq!(
+            Err(radix::DecodeError::InvalidCharacter { byte: b'g', offset: 3 }),
+            radix::try_base16_to_bytes("abcgef")
+        );
+
+        // The offset points into the original string, not the zero-padded one
+        assert_eq!(
+            Err(radix::DecodeError::InvalidCharacter { byte: b'x', offset: 1 }),
+            radix::try_base16_to_bytes("0x1")
+        );
+    }
+
+    #[test]
+    fn test_try_base64_to_bytes() {
+        assert_eq!(Ok(b"Man".to_vec()), radix::try_base64_to_bytes("TWFu"));
+        assert_eq!(Ok(vec![]), radix::try_base64_to_bytes(""));
+
+        assert_eq!(
+            Err(radix::DecodeError::InvalidLength { offset: 3 }),
+            radix::try_base64_to_bytes("TWF")
+        );
+
+        assert_eq!(
+            Err(radix::DecodeError::InvalidCharacter { byte: b'-', offset: 2 }),
+            radix::try_base64_to_bytes("TW-u")
+        );
+
+        // Padding in the middle of a quantum, and padding before the last one
+        assert_eq!(
+            Err(radix::DecodeError::InvalidPadding { offset: 1 }),
+            radix::try_base64_to_bytes("T=Fu")
+        );
+        assert_eq!(
+            Err(radix::DecodeError::InvalidPadding { offset: 2 }),
+            radix::try_base64_to_bytes("TQ==TWFu")
+        );
+
+        // "TR==" and "TWF=" decode to the same bytes as "TQ==" and "TWE=" if
+        // the spare bits are ignored
+        assert_eq!(
+            Err(radix::DecodeError::NonCanonicalTrailingBits { offset: 1 }),
+            radix::try_base64_to_bytes("TR==")
+        );
+        assert_eq!(
+            Err(radix::DecodeError::NonCanonicalTrailingBits { offset: 2 }),
+            radix::try_base64_to_bytes("TWF=")
+        );
+    }
+
+    #[test]
+    fn test_base64_configs() {
+        use radix::Base64Config;
+
+        let bytes = [0xfb, 0xff];
+
+        assert_eq!("+/8=", radix::bytes_to_base64_with(&bytes, Base64Config::STANDARD));
+        assert_eq!("+/8", radix::bytes_to_base64_with(&bytes, Base64Config::STANDARD_NO_PAD));
+        assert_eq!("-_8=", radix::bytes_to_base64_with(&bytes, Base64Config::URL_SAFE));
+        assert_eq!("-_8", radix::bytes_to_base64_with(&bytes, Base64Config::URL_SAFE_NO_PAD));
+
+        assert_eq!(Ok(bytes.to_vec()), radix::try_base64_to_bytes_with("-_8=", Base64Config::URL_SAFE));
+        assert_eq!(Ok(bytes.to_vec()), radix::try_base64_to_bytes_with("-_8", Base64Config::URL_SAFE_NO_PAD));
+        assert_eq!(Ok(bytes.to_vec()), radix::try_base64_to_bytes_with("+/8", Base64Config::STANDARD_NO_PAD));
+
+        assert_eq!(Ok(b"M".to_vec()), radix::try_base64_to_bytes_with("TQ", Base64Config::URL_SAFE_NO_PAD));
+        assert_eq!(Ok(b"Man".to_vec()), radix::try_base64_to_bytes_with("TWFu", Base64Config::URL_SAFE_NO_PAD));
+
+        // Wrong alphabet
+        assert_eq!(
+            Err(radix::DecodeError::InvalidCharacter { byte: b'+', offset: 0 }),
+            radix::try_base64_to_bytes_with("+/8=", Base64Config::URL_SAFE)
+        );
+
+        // Padding where there should be none, and the other way around
+        assert_eq!(
+            Err(radix::DecodeError::InvalidPadding { offset: 3 }),
+            radix::try_base64_to_bytes_with("-_8=", Base64Config::URL_SAFE_NO_PAD)
+        );
+        assert_eq!(
+            Err(radix::DecodeError::InvalidLength { offset: 3 }),
+            radix::try_base64_to_bytes_with("-_8", Base64Config::URL_SAFE)
+        );
+
+        // One leftover character can never be decoded
+        assert_eq!(
+            Err(radix::DecodeError::InvalidLength { offset: 5 }),
+            radix::try_base64_to_bytes_with("TWFuT", Base64Config::URL_SAFE_NO_PAD)
+        );
+
+        // Trailing bits are still checked
+        assert_eq!(
+            Err(radix::DecodeError::NonCanonicalTrailingBits { offset: 1 }),
+            radix::try_base64_to_bytes_with("TR", Base64Config::URL_SAFE_NO_PAD)
+        );
+    }
+
+    #[test]
+    fn test_try_mime_base64_to_bytes() {
+        assert_eq!(
+            Ok(b"With one padding!".to_vec()),
+            radix::try_mime_base64_to_bytes("V2l0aCBvbmUg\r\ncGFkZGlu\nZyE=\n")
+        );
+
+        assert_eq!(
+            Ok(b"Man".to_vec()),
+            radix::try_mime_base64_to_bytes(" TW\tFu ")
+        );
+
+        // Offsets point into the original string, whitespace included
+        assert_eq!(
+            Err(radix::DecodeError::InvalidCharacter { byte: b'-', offset: 6 }),
+            radix::try_mime_base64_to_bytes("TW\r\nFu-A==")
+        );
+
+        assert_eq!(
+            Err(radix::DecodeError::InvalidLength { offset: 5 }),
+            radix::try_mime_base64_to_bytes("TW\nF\n")
+        );
+    }
+
+    #[test]
+    fn test_bytes_to_base64_wrapped() {
+        assert_eq!("TWFu\n", radix::bytes_to_base64_wrapped(b"Man", 76));
+        assert_eq!("TW\nFu\n", radix::bytes_to_base64_wrapped(b"Man", 2));
+        assert_eq!("TWF\nu\n", radix::bytes_to_base64_wrapped(b"Man", 3));
+        assert_eq!("TWFu", radix::bytes_to_base64_wrapped(b"Man", 0));
+        assert_eq!("", radix::bytes_to_base64_wrapped(b"", 64));
+    }
+
+    #[test]
+    fn test_mime_base64_round_trip() {
+        let contents = fs::read_to_string(
+            path::PathBuf::from("./src/set01/input/_break_repeating_key_xor.txt")
+        ).expect("could not open the file");
+
+        let bytes = radix::mime_base64_to_bytes(&contents);
+
+        assert_eq!(contents, radix::bytes_to_base64_wrapped(&bytes, 60));
+    }
+
+    #[test]
+    fn test_bytes_to_base16() {
+        assert_eq!(
+            "0123456789abcdef", 
+            radix::bytes_to_base16(&[0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef])
+        );
+    }
+
+    #[test]
+    fn test_bytes_to_base64() {
+        // Big thanks to wikipedia for these test cases :) 
+        // https://en.wikipedia.org/wiki/Base64#Examples
+        assert_eq!("TWFu", radix::bytes_to_base64(b"Man"));
+        assert_eq!("TWE=", radix::bytes_to_base64(b"Ma"));
+        assert_eq!("TQ==", radix::bytes_to_base64(b"M"));
+    }
+
+    #[test]
+    fn test_into() {
+        // Anything past what was written is left alone
+        let mut out = [b'.'; 12];
+
+        assert_eq!(8, radix::bytes_to_base64_into(b"Man M", radix::Base64Config::STANDARD, &mut out));
+        assert_eq!(b"TWFuIE0=....", &out);
+
+        assert_eq!(7, radix::bytes_to_base64_into(b"Man M", radix::Base64Config::STANDARD_NO_PAD, &mut out));
+        assert_eq!(b"TWFuIE0=....", &out);
+
+        assert_eq!(6, radix::bytes_to_base16_into(b"Man", &mut out));
+        assert_eq!(b"4d616e0=....", &out);
+
+        let mut out = [0u8; 5];
+
+        assert_eq!(Ok(2), radix::try_base16_to_bytes_into(b"abc", &mut out));
+        assert_eq!([0x0a, 0xbc], out[..2]);
+
+        assert_eq!(Ok(5), radix::try_base64_to_bytes_into(b"TWFuIE0=", radix::Base64Config::STANDARD, &mut out));
+        assert_eq!(b"Man M", &out);
+
+        assert_eq!(
+            Err(radix::DecodeError::InvalidCharacter { byte: b'-', offset: 5 }),
+            radix::try_base64_to_bytes_into(b"TWFuI-0=", radix::Base64Config::STANDARD, &mut out)
+        );
+
+        assert_eq!(
+            Err(radix::DecodeError::InvalidCharacter { byte: b'x', offset: 3 }),
+            radix::try_base16_to_bytes_into(b"abcx", &mut out)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "too short")]
+    fn test_into_short_output() {
+        let mut out = [0u8; 3];
+
+        radix::bytes_to_base64_into(b"Man", radix::Base64Config::STANDARD, &mut out);
+    }
+
+    #[test]
+    fn test_lengths() {
+        for len in 0..10 {
+            let bytes = vec![0xa5; len];
+
+            for config in [radix::Base64Config::STANDARD, radix::Base64Config::URL_SAFE_NO_PAD].iter() {
+                let encoded = radix::bytes_to_base64_with(&bytes, *config);
+
+                assert_eq!(encoded.len(), radix::base64_encoded_len(len, *config));
+                assert!(radix::max_base64_decoded_len(encoded.len()) >= len);
+            }
+
+            assert_eq!(len * 2, radix::base16_encoded_len(len));
+            assert_eq!(len, radix::base16_decoded_len(len * 2));
+        }
+
+        // Exact without padding
+        assert_eq!(2, radix::max_base64_decoded_len(3));
+    }
+
+    #[test]
+    fn test_tetrad_to_base16() {
+        assert_eq!(b'0', radix::tetrad_to_base16(&0));
+        assert_eq!(b'9', radix::tetrad_to_base16(&9));
+        assert_eq!(b'a', radix::tetrad_to_base16(&10));
+        assert_eq!(b'f', radix::tetrad_to_base16(&15));
+    }
+
+    #[test]
+    fn test_base16_to_tetrad() {
+        assert_eq!(Ok(0), radix::base16_to_tetrad(&b'0', 0));
+        assert_eq!(Ok(9), radix::base16_to_tetrad(&b'9', 0));
+
+        assert_eq!(Ok(10), radix::base16_to_tetrad(&b'a', 0));
+        assert_eq!(Ok(10), radix::base16_to_tetrad(&b'A', 0));
+
+        assert_eq!(Ok(15), radix::base16_to_tetrad(&b'f', 0));
+        assert_eq!(Ok(15), radix::base16_to_tetrad(&b'F', 0));
+
+        assert_eq!(
+            Err(radix::DecodeError::InvalidCharacter { byte: b'g', offset: 7 }),
+            radix::base16_to_tetrad(&b'g', 7)
+        );
+    }
+
+    #[test]
+    fn test_sextet_to_base64() {
+        use radix::Base64Alphabet::*;
+
+        assert_eq!(b'A', radix::sextet_to_base64(&0, Standard));
+        assert_eq!(b'Z', radix::sextet_to_base64(&25, Standard));
+
+        assert_eq!(b'a', radix::sextet_to_base64(&26, Standard));
+        assert_eq!(b'z', radix::sextet_to_base64(&51, Standard));
+
+        assert_eq!(b'0', radix::sextet_to_base64(&52, Standard));
+        assert_eq!(b'9', radix::sextet_to_base64(&61, Standard));
+
+        assert_eq!(b'+', radix::sextet_to_base64(&62, Standard));
+        assert_eq!(b'/', radix::sextet_to_base64(&63, Standard));
+
+        assert_eq!(b'-', radix::sextet_to_base64(&62, UrlSafe));
+        assert_eq!(b'_', radix::sextet_to_base64(&63, UrlSafe));
+    }
+
+    #[test]
+    fn test_base64_to_sextet() {
+        use radix::Base64Alphabet::*;
+
+        assert_eq!(Ok(0), radix::base64_to_sextet(&b'A', 0, Standard));
+        assert_eq!(Ok(25), radix::base64_to_sextet(&b'Z', 0, Standard));
+
+        assert_eq!(Ok(26), radix::base64_to_sextet(&b'a', 0, Standard));
+        assert_eq!(Ok(51), radix::base64_to_sextet(&b'z', 0, Standard));
+
+        assert_eq!(Ok(52), radix::base64_to_sextet(&b'0', 0, Standard));
+        assert_eq!(Ok(61), radix::base64_to_sextet(&b'9', 0, Standard));
+
+        assert_eq!(Ok(62), radix::base64_to_sextet(&b'+', 0, Standard));
+        assert_eq!(Ok(63), radix::base64_to_sextet(&b'/', 0, Standard));
+
+        assert_eq!(
+            Err(radix::DecodeError::InvalidPadding { offset: 5 }),
+            radix::base64_to_sextet(&b'=', 5, Standard)
+        );
+
+        assert_eq!(Ok(62), radix::base64_to_sextet(&b'-', 0, UrlSafe));
+        assert_eq!(Ok(63), radix::base64_to_sextet(&b'_', 0, UrlSafe));
+
+        assert_eq!(
+            Err(radix::DecodeError::InvalidCharacter { byte: b'+', offset: 3 }),
+            radix::base64_to_sextet(&b'+', 3, UrlSafe)
+        );
+
+        assert_eq!(
+            Err(radix::DecodeError::InvalidCharacter { byte: b'-', offset: 7 }),
+            radix::base64_to_sextet(&b'-', 7, Standard)
+        );
+    }
+}
\ No newline at end of file
@@ -0,0 +1,682 @@
+//! Streaming base16/base64 conversions
+//!
+//! The functions in `radix` want the whole input in memory. That is fine for
+//! the challenge files, but not for a multi-gigabyte capture.
+//! The types here wrap an `io::Write` (encoders) or an `io::Read` (decoders)
+//! and convert the bytes as they go by. Since base64 works on 3-byte groups
+//! (and 4-character quanta) that will rarely line up with whatever buffer size
+//! the caller uses, the leftovers are carried over to the next call.
+//!
+//! They plug straight into `io::copy`:
+//!
+//! ```
+//! use std::io;
+//! use ez_cryptopals::radix::stream::Base64Decoder;
+//!
+//! let mut decoder = Base64Decoder::new_mime(&b"TWFu\nTWE=\n"[..]);
+//! let mut bytes = Vec::new();
+//!
+//! io::copy(&mut decoder, &mut bytes).unwrap();
+//!
+//! assert_eq!(b"ManMa".to_vec(), bytes);
+//! ```
+//!
+//! Decoding errors come back as `io::ErrorKind::InvalidData`, with the
+//! `radix::DecodeError` inside.
+
+use std::cmp;
+use std::io;
+use std::io::prelude::*;
+
+use crate::radix;
+use crate::radix::{Base64Config, DecodeError};
+
+/// How much to read from the inner reader at a time. This is also the size
+/// of the stack buffers the encoders write into
+const CHUNK_SIZE: usize = 4096;
+
+/// Base64 encode everything written to it, and pass it on to `inner`
+///
+/// A trailing partial group can only be encoded once we know no more bytes
+/// are coming, so call `finish` when done. Dropping the encoder will also try
+/// to finish, but any error is lost.
+pub struct Base64Encoder<W: Write> {
+    inner: Option<W>,
+    config: Base64Config,
+    /// Bytes that do not make up a whole 3-byte group yet
+    pending: [u8; 3],
+    pending_len: usize,
+    /// 0 means no wrapping
+    line_width: usize,
+    column: usize,
+    /// Set once `finish` has had a go at the tail, so that `Drop` doesn't
+    /// write a second one if it failed
+    finished: bool,
+}
+
+impl<W: Write> Base64Encoder<W> {
+    /// Create an encoder that writes a single line of base64
+    pub fn new(inner: W, config: Base64Config) -> Self {
+        Base64Encoder {
+            inner: Some(inner),
+            config,
+            pending: [0; 3],
+            pending_len: 0,
+            line_width: 0,
+            column: 0,
+            finished: false,
+        }
+    }
+
+    /// Create an encoder that breaks the output into lines the way
+    /// `radix::bytes_to_base64_wrapped` does
+    pub fn new_wrapped(inner: W, line_width: usize) -> Self {
+        let mut encoder = Base64Encoder::new(inner, Base64Config::STANDARD);
+        encoder.line_width = line_width;
+
+        encoder
+    }
+
+    /// Encode whatever is left over (with padding) and hand back the inner
+    /// writer
+    pub fn finish(mut self) -> io::Result<W> {
+        self.finished = true;
+        self.write_tail()?;
+
+        Ok(self.inner.take().unwrap())
+    }
+
+    fn write_tail(&mut self) -> io::Result<()> {
+        let mut tail = [0u8; 4];
+        let len = radix::bytes_to_base64_into(
+            &self.pending[..self.pending_len],
+            self.config,
+            &mut tail
+        );
+
+        // Every line ends with a new line, the last one too
+        self.write_encoded(&tail[..len], true)?;
+        self.pending_len = 0;
+
+        Ok(())
+    }
+
+    /// Write already encoded characters out, breaking lines as needed
+    ///
+    /// The lines are laid out in a buffer and written in one go, and the
+    /// column only moves on once `inner` has taken them, so after an error
+    /// the same characters can be written again.
+    fn write_encoded(&mut self, mut encoded: &[u8], end_line: bool) -> io::Result<()> {
+        let line_width = self.line_width;
+
+        if line_width == 0 {
+            return self.inner_mut().write_all(encoded);
+        }
+
+        // At worst, a new line after every character
+        let mut lines = [0u8; 2 * CHUNK_SIZE];
+        let mut len = 0;
+        let mut column = self.column;
+
+        while !encoded.is_empty() {
+            let count = cmp::min(line_width - column, encoded.len());
+
+            lines[len..len + count].copy_from_slice(&encoded[..count]);
+            len += count;
+
+            column += count;
+            encoded = &encoded[count..];
+
+            if column == line_width {
+                column = 0;
+                lines[len] = b'\n';
+                len += 1;
+            }
+        }
+
+        if end_line && column > 0 {
+            column = 0;
+            lines[len] = b'\n';
+            len += 1;
+        }
+
+        self.inner_mut().write_all(&lines[..len])?;
+        self.column = column;
+
+        Ok(())
+    }
+
+    fn inner_mut(&mut self) -> &mut W {
+        // `inner` is only ever taken by `finish`, which consumes the encoder
+        self.inner.as_mut().unwrap()
+    }
+}
+
+impl<W: Write> Write for Base64Encoder<W> {
+    /// Takes at most a buffer's worth per call, and nothing at all if it
+    /// fails
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // Top up the group left over from the previous call first
+        if self.pending_len > 0 {
+            let count = cmp::min(3 - self.pending_len, buf.len());
+
+            let mut group = self.pending;
+            group[self.pending_len..self.pending_len + count].copy_from_slice(&buf[..count]);
+
+            if self.pending_len + count == 3 {
+                let mut encoded = [0u8; 4];
+                let len = radix::bytes_to_base64_into(&group, self.config, &mut encoded);
+
+                self.write_encoded(&encoded[..len], false)?;
+                self.pending_len = 0;
+            }
+            else {
+                self.pending = group;
+                self.pending_len += count;
+            }
+
+            return Ok(count);
+        }
+
+        // Encode as many whole groups as fit in the buffer, or hold on to
+        // what is left if there aren't any
+        let whole = cmp::min(buf.len() - buf.len() % 3, CHUNK_SIZE / 4 * 3);
+
+        if whole == 0 {
+            self.pending[..buf.len()].copy_from_slice(buf);
+            self.pending_len = buf.len();
+
+            return Ok(buf.len());
+        }
+
+        let mut encoded = [0u8; CHUNK_SIZE];
+        let len = radix::bytes_to_base64_into(&buf[..whole], self.config, &mut encoded);
+
+        self.write_encoded(&encoded[..len], false)?;
+
+        Ok(whole)
+    }
+
+    /// Flush the inner writer
+    /// This can not flush a partial group. Only `finish` can do that.
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner_mut().flush()
+    }
+}
+
+impl<W: Write> Drop for Base64Encoder<W> {
+    fn drop(&mut self) {
+        if !self.finished {
+            let _ = self.write_tail();
+        }
+    }
+}
+
+/// Decode the base64 read from `inner`
+pub struct Base64Decoder<R: Read> {
+    inner: R,
+    config: Base64Config,
+    skip_whitespace: bool,
+    /// Characters that do not make up a whole quantum yet
+    pending: Vec<u8>,
+    /// Where each of the `pending` characters was in the input
+    pending_offsets: Vec<usize>,
+    /// Decoded bytes that have not been read out yet
+    decoded: Vec<u8>,
+    decoded_pos: usize,
+    /// How many bytes have been read from `inner` so far
+    offset: usize,
+    /// Where the padding is, once we have decoded a quantum with some.
+    /// Nothing is allowed to come after it.
+    padding_offset: Option<usize>,
+    eof: bool,
+}
+
+impl<R: Read> Base64Decoder<R> {
+    /// Create a decoder that expects nothing but base64 characters
+    pub fn new(inner: R, config: Base64Config) -> Self {
+        Base64Decoder {
+            inner,
+            config,
+            skip_whitespace: false,
+            pending: Vec::new(),
+            pending_offsets: Vec::new(),
+            decoded: Vec::new(),
+            decoded_pos: 0,
+            offset: 0,
+            padding_offset: None,
+            eof: false,
+        }
+    }
+
+    /// Create a decoder that skips whitespace and line breaks the way
+    /// `radix::mime_base64_to_bytes` does
+    pub fn new_mime(inner: R) -> Self {
+        Base64Decoder {
+            skip_whitespace: true,
+            ..Base64Decoder::new(inner, Base64Config::STANDARD)
+        }
+    }
+
+    /// Read another chunk from `inner` and decode as much of it as we can
+    fn fill(&mut self) -> io::Result<()> {
+        let mut chunk = [0u8; CHUNK_SIZE];
+
+        let read = read_retrying(&mut self.inner, &mut chunk)?;
+
+        self.decoded.clear();
+        self.decoded_pos = 0;
+
+        if read == 0 {
+            self.eof = true;
+
+            // Whatever is left is the last quantum. Decoding will complain
+            // if it is not allowed to be short.
+            if !self.pending.is_empty() {
+                self.decode_pending(self.pending.len())?;
+            }
+
+            return Ok(());
+        }
+
+        for (i, c) in chunk[..read].iter().enumerate() {
+            if self.skip_whitespace && radix::is_base64_whitespace(c) {
+                continue;
+            }
+
+            if let Some(offset) = self.padding_offset {
+                return Err(invalid_data(DecodeError::InvalidPadding { offset }));
+            }
+
+            self.pending.push(*c);
+            self.pending_offsets.push(self.offset + i);
+        }
+
+        self.offset += read;
+
+        let whole = self.pending.len() - self.pending.len() % 4;
+
+        if whole > 0 {
+            self.padding_offset = self.pending[..whole].iter()
+                                                        .position(|c| *c == b'=')
+                                                        .map(|index| self.pending_offsets[index]);
+
+            // Only the last quantum can have padding. If anything is in a
+            // quantum before it, decoding will catch it.
+            self.decode_pending(whole)?;
+        }
+
+        Ok(())
+    }
+
+    /// Decode the first `count` pending characters into `decoded`
+    fn decode_pending(&mut self, count: usize) -> io::Result<()> {
+        self.decoded.resize(radix::max_base64_decoded_len(count), 0);
+
+        let pending_offsets = &self.pending_offsets;
+        let end = self.offset;
+
+        let written = radix::try_base64_to_bytes_into(&self.pending[..count], self.config, &mut self.decoded)
+            .map_err(|e| {
+                // Point back into what was read from `inner`, skipped
+                // whitespace and all
+                invalid_data(e.map_offset(|index| {
+                    if index < count { pending_offsets[index] } else { end }
+                }))
+            })?;
+
+        self.decoded.truncate(written);
+
+        self.pending.drain(..count);
+        self.pending_offsets.drain(..count);
+
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for Base64Decoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.decoded_pos == self.decoded.len() && !self.eof {
+            self.fill()?;
+        }
+
+        Ok(copy_out(&self.decoded, &mut self.decoded_pos, buf))
+    }
+}
+
+/// Base16 encode everything written to it, and pass it on to `inner`
+///
+/// Every byte turns into exactly two characters, so there is nothing to carry
+/// over and no need to finish.
+pub struct HexEncoder<W: Write> {
+    inner: W,
+}
+
+impl<W: Write> HexEncoder<W> {
+    pub fn new(inner: W) -> Self {
+        HexEncoder { inner }
+    }
+
+    /// Hand back the inner writer
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for HexEncoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut encoded = [0u8; CHUNK_SIZE];
+
+        for bytes in buf.chunks(CHUNK_SIZE / 2) {
+            let len = radix::bytes_to_base16_into(bytes, &mut encoded);
+            self.inner.write_all(&encoded[..len])?;
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Decode the base16 read from `inner`
+///
+/// Unlike `radix::base16_to_bytes`, an odd number of characters is an error.
+/// By the time we find out the length is odd, it is too late to pretend
+/// there was a leading 0.
+pub struct HexDecoder<R: Read> {
+    inner: R,
+    /// The high tetrad of a byte whose low tetrad has not been read yet
+    pending: Option<u8>,
+    decoded: Vec<u8>,
+    decoded_pos: usize,
+    offset: usize,
+    eof: bool,
+}
+
+impl<R: Read> HexDecoder<R> {
+    pub fn new(inner: R) -> Self {
+        HexDecoder {
+            inner,
+            pending: None,
+            decoded: Vec::new(),
+            decoded_pos: 0,
+            offset: 0,
+            eof: false,
+        }
+    }
+
+    fn fill(&mut self) -> io::Result<()> {
+        let mut chunk = [0u8; CHUNK_SIZE];
+
+        let read = read_retrying(&mut self.inner, &mut chunk)?;
+
+        self.decoded.clear();
+        self.decoded_pos = 0;
+
+        if read == 0 {
+            self.eof = true;
+
+            if self.pending.is_some() {
+                return Err(invalid_data(
+                    DecodeError::InvalidLength { offset: self.offset }
+                ));
+            }
+
+            return Ok(());
+        }
+
+        for (i, c) in chunk[..read].iter().enumerate() {
+            let tetrad = radix::base16_to_tetrad(c, self.offset + i)
+                                .map_err(invalid_data)?;
+
+            match self.pending.take() {
+                Some(high) => self.decoded.push(high << 4 | tetrad),
+                None => self.pending = Some(tetrad),
+            }
+        }
+
+        self.offset += read;
+
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for HexDecoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.decoded_pos == self.decoded.len() && !self.eof {
+            self.fill()?;
+        }
+
+        Ok(copy_out(&self.decoded, &mut self.decoded_pos, buf))
+    }
+}
+
+/// Copy as much of `decoded[*pos..]` as fits into `buf`
+fn copy_out(decoded: &[u8], pos: &mut usize, buf: &mut [u8]) -> usize {
+    let count = cmp::min(buf.len(), decoded.len() - *pos);
+
+    buf[..count].copy_from_slice(&decoded[*pos..*pos + count]);
+    *pos += count;
+
+    count
+}
+
+/// `Read::read`, but trying again when interrupted
+fn read_retrying<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    loop {
+        match reader.read(buf) {
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            result => return result,
+        }
+    }
+}
+
+fn invalid_data(e: DecodeError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use std::fs;
+    use std::io;
+    use std::io::prelude::*;
+    use std::path;
+
+    use crate::radix;
+    use crate::radix::{Base64Config, DecodeError};
+    use crate::radix::stream;
+
+    /// Hands out at most one byte per `read`, so that every quantum is split
+    /// across calls
+    struct Trickle<'a>(&'a [u8]);
+
+    impl<'a> Read for Trickle<'a> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.0.is_empty() || buf.is_empty() {
+                return Ok(0);
+            }
+
+            buf[0] = self.0[0];
+            self.0 = &self.0[1..];
+
+            Ok(1)
+        }
+    }
+
+    fn decode_error(e: io::Error) -> DecodeError {
+        assert_eq!(io::ErrorKind::InvalidData, e.kind());
+
+        *e.into_inner().unwrap().downcast::<DecodeError>().unwrap()
+    }
+
+    #[test]
+    fn test_base64_encoder() {
+        let mut encoder = stream::Base64Encoder::new(Vec::new(), Base64Config::STANDARD);
+
+        // Split up so that groups straddle the writes
+        encoder.write_all(b"M").unwrap();
+        encoder.write_all(b"anM").unwrap();
+        encoder.write_all(b"a").unwrap();
+
+        assert_eq!(b"TWFuTWE=".to_vec(), encoder.finish().unwrap());
+
+        let mut encoder = stream::Base64Encoder::new(Vec::new(), Base64Config::URL_SAFE_NO_PAD);
+        encoder.write_all(&[0xfb, 0xff]).unwrap();
+
+        assert_eq!(b"-_8".to_vec(), encoder.finish().unwrap());
+    }
+
+    #[test]
+    fn test_base64_encoder_drop_finishes() {
+        let mut encoded = Vec::new();
+
+        {
+            let mut encoder = stream::Base64Encoder::new(&mut encoded, Base64Config::STANDARD);
+            encoder.write_all(b"Ma").unwrap();
+        }
+
+        assert_eq!(b"TWE=".to_vec(), encoded);
+    }
+
+    /// A writer that fails the first write, and keeps everything after that
+    struct FailOnce<'a> {
+        failed: bool,
+        written: &'a mut Vec<u8>,
+    }
+
+    impl<'a> Write for FailOnce<'a> {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if !self.failed {
+                self.failed = true;
+
+                return Err(io::Error::other("full"));
+            }
+
+            self.written.extend_from_slice(buf);
+
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_base64_encoder_failed_write_can_be_retried() {
+        let mut written = Vec::new();
+
+        let mut encoder = stream::Base64Encoder::new_wrapped(FailOnce { failed: false, written: &mut written }, 4);
+        encoder.write_all(b"M").unwrap();
+
+        // Nothing was taken, so the same bytes go again
+        assert!(encoder.write(b"anMa").is_err());
+        encoder.write_all(b"anMa").unwrap();
+        encoder.finish().unwrap();
+
+        assert_eq!(b"TWFu\nTWE=\n".to_vec(), written);
+    }
+
+    #[test]
+    fn test_base64_encoder_failed_finish_writes_nothing_more() {
+        let mut written = Vec::new();
+
+        let mut encoder = stream::Base64Encoder::new_wrapped(FailOnce { failed: false, written: &mut written }, 76);
+        encoder.write_all(b"Ma").unwrap();
+        assert!(encoder.finish().is_err());
+
+        // Dropping it after the error didn't write another tail
+        assert!(written.is_empty());
+    }
+
+    #[test]
+    fn test_base64_round_trip_through_copy() {
+        let contents = fs::read_to_string(
+            path::PathBuf::from("./src/set01/input/_break_repeating_key_xor.txt")
+        ).expect("could not open the file");
+
+        let mut decoder = stream::Base64Decoder::new_mime(Trickle(contents.as_bytes()));
+        let mut bytes = Vec::new();
+
+        io::copy(&mut decoder, &mut bytes).unwrap();
+
+        assert_eq!(radix::mime_base64_to_bytes(&contents), bytes);
+
+        let mut encoder = stream::Base64Encoder::new_wrapped(Vec::new(), 60);
+
+        io::copy(&mut Trickle(&bytes), &mut encoder).unwrap();
+
+        assert_eq!(contents.into_bytes(), encoder.finish().unwrap());
+    }
+
+    #[test]
+    fn test_base64_decoder_unpadded() {
+        let mut decoder = stream::Base64Decoder::new(
+            Trickle(b"TWFuTQ"),
+            Base64Config::URL_SAFE_NO_PAD
+        );
+        let mut bytes = Vec::new();
+
+        decoder.read_to_end(&mut bytes).unwrap();
+
+        assert_eq!(b"ManM".to_vec(), bytes);
+    }
+
+    #[test]
+    fn test_base64_decoder_errors() {
+        let mut bytes = Vec::new();
+
+        let mut decoder = stream::Base64Decoder::new(Trickle(b"TWFuTW-u"), Base64Config::STANDARD);
+        assert_eq!(
+            DecodeError::InvalidCharacter { byte: b'-', offset: 6 },
+            decode_error(decoder.read_to_end(&mut bytes).unwrap_err())
+        );
+
+        let mut decoder = stream::Base64Decoder::new(Trickle(b"TWFuTWF"), Base64Config::STANDARD);
+        assert_eq!(
+            DecodeError::InvalidLength { offset: 7 },
+            decode_error(decoder.read_to_end(&mut bytes).unwrap_err())
+        );
+
+        // Nothing is allowed after the padding, even in a later read
+        let mut decoder = stream::Base64Decoder::new_mime(Trickle(b"TQ==\nTWFu\n"));
+        assert_eq!(
+            DecodeError::InvalidPadding { offset: 2 },
+            decode_error(decoder.read_to_end(&mut bytes).unwrap_err())
+        );
+    }
+
+    #[test]
+    fn test_hex_round_trip() {
+        let mut encoder = stream::HexEncoder::new(Vec::new());
+        io::copy(&mut Trickle(&[0xde, 0xad, 0xbe, 0xef]), &mut encoder).unwrap();
+
+        let encoded = encoder.into_inner();
+        assert_eq!(b"deadbeef".to_vec(), encoded);
+
+        let mut decoder = stream::HexDecoder::new(Trickle(&encoded));
+        let mut bytes = Vec::new();
+        io::copy(&mut decoder, &mut bytes).unwrap();
+
+        assert_eq!(vec![0xde, 0xad, 0xbe, 0xef], bytes);
+    }
+
+    #[test]
+    fn test_hex_decoder_errors() {
+        let mut bytes = Vec::new();
+
+        let mut decoder = stream::HexDecoder::new(Trickle(b"deadbeeg"));
+        assert_eq!(
+            DecodeError::InvalidCharacter { byte: b'g', offset: 7 },
+            decode_error(decoder.read_to_end(&mut bytes).unwrap_err())
+        );
+
+        let mut decoder = stream::HexDecoder::new(Trickle(b"deadbee"));
+        assert_eq!(
+            DecodeError::InvalidLength { offset: 7 },
+            decode_error(decoder.read_to_end(&mut bytes).unwrap_err())
+        );
+    }
+}
@@ -0,0 +1,155 @@
+//! # Where random bytes come from
+//!
+//! Keys, IVs and the challenge 11 oracle all need random bytes. Straight
+//! from the OS is what they should get normally, but then a run that fails
+//! can never be repeated. So anything random here takes a `RandomSource`
+//! (in its `_with` version): `OsRandom` for the real thing, or a
+//! `SeededRandom` to get the exact same bytes every time for a given seed.
+//!
+//! ```
+//! use ez_cryptopals::aes128::Key;
+//! use ez_cryptopals::random::SeededRandom;
+//!
+//! let first: Key = Key::random_with(&mut SeededRandom::new(11));
+//! let again: Key = Key::random_with(&mut SeededRandom::new(11));
+//!
+//! assert_eq!(first, again);
+//! ```
+
+#[cfg(feature = "openssl")]
+use openssl::rand;
+
+/// Something that hands out random bytes
+pub trait RandomSource {
+    /// Fill all of `buffer` with random bytes
+    fn fill_bytes(&mut self, buffer: &mut [u8]);
+}
+
+/// So that a source can be lent to something that wants to own one
+impl<R: RandomSource + ?Sized> RandomSource for &mut R {
+    fn fill_bytes(&mut self, buffer: &mut [u8]) {
+        (**self).fill_bytes(buffer);
+    }
+}
+
+/// Random bytes from openssl or, when built without it, from `/dev/urandom`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OsRandom;
+
+impl RandomSource for OsRandom {
+    fn fill_bytes(&mut self, buffer: &mut [u8]) {
+        #[cfg(feature = "openssl")]
+        rand::rand_bytes(buffer).unwrap();
+
+        #[cfg(not(feature = "openssl"))]
+        {
+            use std::io::Read;
+
+            std::fs::File::open("/dev/urandom")
+                .and_then(|mut urandom| urandom.read_exact(buffer))
+                .unwrap_or_else(|e| panic!("error: could not read /dev/urandom - {}", e));
+        }
+    }
+}
+
+/// The same stream of bytes every time for the same seed (SplitMix64)
+///
+/// This is for replaying tests and attacks, *not* for making keys that need
+/// to stay secret: anyone who knows or guesses the seed knows every byte.
+#[derive(Debug, Clone)]
+pub struct SeededRandom {
+    seed: u64,
+    state: u64,
+}
+
+impl SeededRandom {
+    pub fn new(seed: u64) -> Self {
+        SeededRandom { seed, state: seed }
+    }
+
+    /// Seeded from `OsRandom`. Print `seed()` somewhere, and a run that
+    /// goes wrong can be replayed with `new`
+    pub fn from_os() -> Self {
+        let mut seed = [0u8; 8];
+
+        OsRandom.fill_bytes(&mut seed);
+
+        SeededRandom::new(u64::from_le_bytes(seed))
+    }
+
+    /// The seed this started from
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+
+        z ^ (z >> 31)
+    }
+}
+
+impl RandomSource for SeededRandom {
+    fn fill_bytes(&mut self, buffer: &mut [u8]) {
+        for chunk in buffer.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use crate::random::{OsRandom, RandomSource, SeededRandom};
+
+    #[test]
+    fn test_seeded_is_splitmix64() {
+        let mut random = SeededRandom::new(0);
+
+        assert_eq!(0xe220_a839_7b1d_cdaf, random.next_u64());
+        assert_eq!(0x6e78_9e6a_a1b9_65f4, random.next_u64());
+        assert_eq!(0x06c4_5d18_8009_454f, random.next_u64());
+    }
+
+    #[test]
+    fn test_seeded_replays() {
+        let mut first = SeededRandom::from_os();
+        let mut again = SeededRandom::new(first.seed());
+
+        // Odd sizes, so that some of each u64 gets thrown away
+        let mut a = [0u8; 13];
+        let mut b = [0u8; 13];
+
+        first.fill_bytes(&mut a);
+        again.fill_bytes(&mut b);
+        assert_eq!(a, b);
+
+        first.fill_bytes(&mut a[..3]);
+        again.fill_bytes(&mut b[..3]);
+        assert_eq!(a, b);
+
+        // A different seed is a different stream
+        let mut c = [0u8; 13];
+        let mut d = [0u8; 13];
+
+        SeededRandom::new(first.seed()).fill_bytes(&mut c);
+        SeededRandom::new(first.seed().wrapping_add(1)).fill_bytes(&mut d);
+        assert_ne!(c, d);
+    }
+
+    #[test]
+    fn test_os_random() {
+        let mut a = [0u8; 32];
+        let mut b = [0u8; 32];
+
+        OsRandom.fill_bytes(&mut a);
+        OsRandom.fill_bytes(&mut b);
+
+        assert_ne!(a, b);
+    }
+}
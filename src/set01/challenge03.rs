@@ -22,6 +22,12 @@ pub struct EnglishAsciiScorer<'a> {
     char_ranking: &'a [u8],
 }
 
+impl<'a> Default for EnglishAsciiScorer<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<'a> EnglishAsciiScorer<'a> {
     /// Create a new scorer
     pub fn new() -> Self {
@@ -117,7 +123,7 @@ Vec<SingleByteXorDecryptionAttempt>
 
 
     // Sort the results from best score to worst
-    scores.sort_by(|a, b| b.score.cmp(&a.score));
+    scores.sort_by_key(|attempt| std::cmp::Reverse(attempt.score));
 
     scores
 }
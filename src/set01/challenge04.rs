@@ -39,7 +39,7 @@ pub mod test {
 
         let mut best_guess = (0, 0, Vec::new());
 
-        for (_, line) in reader.lines().enumerate() {
+        for line in reader.lines() {
             let line = line.unwrap();
 
             let bytes = radix::base16_to_bytes(&line);
@@ -43,7 +43,7 @@ pub fn edit_distance(lhs: &[u8], rhs: &[u8]) -> u32 {
     for mut byte in xor_result {
         for _ in 0..8 {
             edit_distance += (byte & 0b0000_0001) as u32;
-            byte = byte >> 1;
+            byte >>= 1;
         }
     }
 
@@ -91,11 +91,8 @@ pub mod test {
             )
             .expect("could not open the file");
 
-        // Get rid of the new lines
-        let contents = contents.replace("\r\n", "");
-        let contents = contents.replace("\n", "");
-
-        let bytes = radix::base64_to_bytes(&contents);
+        // The file is wrapped at 60 columns
+        let bytes = radix::mime_base64_to_bytes(&contents);
 
         // 1st Step: Try to make a guess as to what the key size might be
         let min_keysize: u32 = 2;
@@ -133,7 +130,7 @@ pub mod test {
             edit_distance_map[keysize as usize - min_keysize as usize] = (average_edit_distance, keysize);
         }
         
-        edit_distance_map.sort_by(|p1, p2| p1.0.cmp(&p2.0));
+        edit_distance_map.sort_by_key(|p| p.0);
 
         // 2nd step: Break the repeating key xor.
         // One way to do this is to take our keysize guess and use it to break up the cipher text
@@ -184,17 +181,17 @@ pub mod test {
     fn test_count_off_and_partition() {
         assert_eq!(
             vec![vec![1,2,3]],
-            challenge06::count_off_and_partition(1, &vec![1,2,3])
+            challenge06::count_off_and_partition(1, &[1,2,3])
         );
 
         assert_eq!(
             vec![vec![1,3,5], vec![2,4]],
-            challenge06::count_off_and_partition(2, &vec![1,2,3,4,5])
+            challenge06::count_off_and_partition(2, &[1,2,3,4,5])
         );
 
         assert_eq!(
             vec![vec![1,4,7], vec![2,5,8], vec![3,6,9]],
-            challenge06::count_off_and_partition(3, &vec![1,2,3,4,5,6,7,8,9])
+            challenge06::count_off_and_partition(3, &[1,2,3,4,5,6,7,8,9])
         );
     }
 }
\ No newline at end of file
@@ -16,16 +16,13 @@ pub mod tests {
             path::PathBuf::from("./src/set01/input/_aes_in_ecb_mode.txt")
         ).expect("could not open file");
 
-        // Get rid of the new lines
-        let cipher_text = cipher_text.replace("\r\n", "");
-        let cipher_text = cipher_text.replace("\n", "");
-
-        let cipher_text_bytes = radix::base64_to_bytes(&cipher_text);
+        // The file is wrapped at 60 columns
+        let cipher_text_bytes = radix::mime_base64_to_bytes(&cipher_text);
 
 
         let plain_text_bytes = aes128::ecb_decrypt(
-            b"YELLOW SUBMARINE",
-            &cipher_text_bytes);
+            &aes128::Key::from_bytes(*b"YELLOW SUBMARINE"),
+            &cipher_text_bytes).unwrap();
 
         let plain_text = String::from_utf8(plain_text_bytes).unwrap();
 
@@ -32,7 +32,7 @@ pub mod test {
 
         let reader = io::BufReader::new(f);
 
-        let mut best_guess = (0, u32::max_value(), Vec::new());
+        let mut best_guess = (0, u32::MAX, Vec::new());
 
         for (i, line) in reader.lines().enumerate() {
             let line = line.unwrap();
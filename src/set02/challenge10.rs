@@ -16,18 +16,15 @@ pub mod test {
             PathBuf::from("./src/set02/input/_implement_cbc_mode.txt")
         ).expect("could not read file");
 
-        // remove new lines
-        let contents = contents.replace("\n", "");
-        let contents = contents.replace("\r\n", "");
-
-        let content_bytes = radix::base64_to_bytes(
+        // The file is wrapped at 60 columns
+        let content_bytes = radix::mime_base64_to_bytes(
             &contents
         );
 
         let plain_text = aes128::cbc_decrypt(
-            b"YELLOW SUBMARINE",
-            &[0; 16], 
-            &content_bytes);
+            &aes128::Key::from_bytes(*b"YELLOW SUBMARINE"),
+            &aes128::Iv::from_bytes([0; 16]),
+            &content_bytes).unwrap();
 
         assert!(
             std::str::from_utf8(
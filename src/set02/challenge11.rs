@@ -16,8 +16,7 @@
 //! learn more about what encryption it is using.
 
 use crate::aes128;
-
-use openssl::rand;
+use crate::random::{OsRandom, RandomSource};
 
 pub struct OracleResult {
     cipher_text: Vec<u8>,
@@ -28,30 +27,38 @@ pub struct OracleResult {
 /// cipher mode specified
 pub fn cipher_text_oracle(msg: &[u8]) -> OracleResult 
 {
-    let random_key = aes128::get_random_key();
+    cipher_text_oracle_with(msg, &mut OsRandom)
+}
+
+/// `cipher_text_oracle`, with the key, the mode, the IV and the bytes added
+/// around the message all coming from `random`. With a `SeededRandom`, the
+/// same seed makes the same cipher text
+pub fn cipher_text_oracle_with<R: RandomSource + ?Sized>(msg: &[u8], random: &mut R) -> OracleResult
+{
+    let random_key = aes128::get_random_key_with(random);
     
-    let mut buf1 = [0 as u8; 1];
-    let mut buf2 = [0 as u8; 1];
+    let mut buf1 = [0u8; 1];
+    let mut buf2 = [0u8; 1];
 
-    rand::rand_bytes(&mut buf1).unwrap();
-    rand::rand_bytes(&mut buf2).unwrap();
+    random.fill_bytes(&mut buf1);
+    random.fill_bytes(&mut buf2);
 
     let prefix_count = (buf1[0] % 6) + 5;
     let suffix_count = (buf2[0] % 6) + 5;
 
-    let mut prefix_buf = vec![0 as u8; prefix_count as usize];
-    let mut suffix_buf = vec![0 as u8; suffix_count as usize];
+    let mut prefix_buf = vec![0u8; prefix_count as usize];
+    let mut suffix_buf = vec![0u8; suffix_count as usize];
 
-    rand::rand_bytes(&mut prefix_buf).unwrap();
-    rand::rand_bytes(&mut suffix_buf).unwrap();
+    random.fill_bytes(&mut prefix_buf);
+    random.fill_bytes(&mut suffix_buf);
 
     let mut padded_msg = Vec::new();
     padded_msg.append(&mut prefix_buf);
     padded_msg.append(&mut msg.to_vec());
     padded_msg.append(&mut suffix_buf);
 
-    let mut buf = [0 as u8; 1];
-    rand::rand_bytes(&mut buf).unwrap();
+    let mut buf = [0u8; 1];
+    random.fill_bytes(&mut buf);
 
     let chance = buf[0] % 2;
     let mode;
@@ -62,8 +69,7 @@ pub fn cipher_text_oracle(msg: &[u8]) -> OracleResult
     }
     else {
         mode = aes128::CipherMode::CBC;
-        let mut init_vector = [0 as u8; 16];
-        rand::rand_bytes(&mut init_vector).unwrap();
+        let init_vector = aes128::Iv::random_with(random);
         aes128::cbc_encrypt(&random_key, &init_vector, &padded_msg)
     };
 
@@ -118,6 +124,7 @@ pub fn any_identical_consecutive_blocks(len: usize, msg: &[u8]) -> bool {
 
 /// An ECB/CBC detection oracle
 pub mod test {
+    use crate::random::SeededRandom;
     use crate::set02::challenge11;
 
     /// Solution to the challenge (see source)
@@ -128,9 +135,13 @@ pub mod test {
         AAAAAAAAAAAAAAAA\
         AAAAAAAAAAAAAAAA";
 
+        // Seeded, so that every run sees the same 100 keys, IVs and modes
+        let mut random = SeededRandom::new(11);
+
         for _ in 0..100 {
-            let oracle_result = challenge11::cipher_text_oracle(
-                plain_text
+            let oracle_result = challenge11::cipher_text_oracle_with(
+                plain_text,
+                &mut random
             );
 
             assert_eq!(
@@ -145,6 +156,20 @@ pub mod test {
         an_ecb_cbc_detection_oracle();
     }
 
+    #[test]
+    pub fn test_oracle_replays() {
+        let mut random = SeededRandom::new(1234);
+        let mut again = SeededRandom::new(random.seed());
+
+        for _ in 0..10 {
+            let first = challenge11::cipher_text_oracle_with(b"ICE ICE BABY", &mut random);
+            let second = challenge11::cipher_text_oracle_with(b"ICE ICE BABY", &mut again);
+
+            assert_eq!(first.cipher_mode, second.cipher_mode);
+            assert_eq!(first.cipher_text, second.cipher_text);
+        }
+    }
+
     #[test]
     pub fn test_any_identical_consecutive_blocks() {
         let msg1 = b"0";
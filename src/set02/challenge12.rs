@@ -24,8 +24,9 @@
 
 use crate::radix;
 use crate::aes128;
+use crate::aes128::Key;
 
-pub fn cipher_text_oracle(key: &[u8; 16], msg: &[u8]) -> Vec<u8> 
+pub fn cipher_text_oracle(key: &Key, msg: &[u8]) -> Vec<u8> 
 {
     // secret message we have to append to the string
     let secret = "\
@@ -40,7 +41,7 @@ pub fn cipher_text_oracle(key: &[u8; 16], msg: &[u8]) -> Vec<u8>
     padded_msg.append(&mut msg.to_vec());
     padded_msg.append(&mut secret_bytes);
 
-    aes128::ecb_encrypt(&key, &padded_msg)
+    aes128::ecb_encrypt(key, &padded_msg)
 }
 
 /// The process is as follows:
@@ -48,15 +49,15 @@ pub fn cipher_text_oracle(key: &[u8; 16], msg: &[u8]) -> Vec<u8>
 /// - Start with a plain text that is one-character long
 /// - Record the length of the ciphertext we get for that plain text
 /// - Repeatedly increase the size of the plain text by one, encrypt and compare
-/// the result to the original length
+///   the result to the original length
 /// - Do this until the length of the current cipher text is different from
-/// that of the original. The difference will be the block size.
+///   that of the original. The difference will be the block size.
 /// 
 /// Disclaimer: I previously used a different (and less precise) technique to
 /// detect the block size. However, after I solved this challenge, I could not
 /// help but check online for other solutions and found this much better one.
 pub fn detect_cipher_block_size() -> usize {
-    let key = [0; 16];
+    let key = Key::from_bytes([0; 16]);
     let mut plain_text = vec![b'A'];
     let original_length = cipher_text_oracle(&key, &plain_text).len();
     let mut current_length = original_length;
@@ -80,7 +81,7 @@ pub mod test {
 
     /// Solution to the challenge (see soource)
     pub fn byte_at_a_time_ecb_decryption() {
-        let random_key = b"0123456789abcdef";
+        let random_key = &aes128::Key::from_bytes(*b"0123456789abcdef");
         
         // First discover the block size
         let block_size = challenge12::detect_cipher_block_size();
@@ -110,11 +111,7 @@ pub mod test {
         for x in 1..=shifting_buffer_len {
             let mut brute_force_table = HashMap::new();
             let shifted_input_len = shifting_buffer_len - x;
-            let mut shifted_input = Vec::new();
-            
-            for _ in 0..shifted_input_len {
-                shifted_input.push(b'A');
-            }
+            let shifted_input = vec![b'A'; shifted_input_len];
 
             print!("{} ", shifted_input_len);
             
@@ -135,7 +132,7 @@ pub mod test {
                     &curr
                 );
 
-                oracle_result.split_off(shifting_buffer_len);
+                oracle_result.truncate(shifting_buffer_len);
 
                 brute_force_table.insert(
                     oracle_result,
@@ -149,7 +146,7 @@ pub mod test {
                 &shifted_input
             );
 
-            oracle_result.split_off(shifting_buffer_len);
+            oracle_result.truncate(shifting_buffer_len);
 
             let mut plain_text = brute_force_table.remove(&oracle_result)
                                                     .unwrap();
@@ -18,8 +18,12 @@
 //! I demonstrate it below
 
 use crate::aes128;
+use crate::aes128::Key;
 
-const SOME_KEY: &[u8; 16] = b"YELLOW SUBMARINE";
+/// The web service's key, which the attacker never gets to see
+fn some_key() -> Key {
+    Key::from_hex("59454c4c4f57205355424d4152494e45")
+}
 
 /// Expands a query string of the form:  
 /// "hello=world&hello=people"  
@@ -44,15 +48,13 @@ pub fn expand_query_string(qs: &str) -> String {
         let key = pair.next();
         let value = pair.next();
 
-        if key.is_none() || value.is_none() {
-            panic!("error: invalid query string");
-        }
-        else {
-            let (key, value) = (key.unwrap(), value.unwrap());
-
-            expanded.push_str(
-                &(format!("\t'{}': '{}',", key, value))
-            );
+        match (key, value) {
+            (Some(key), Some(value)) => {
+                expanded.push_str(
+                    &(format!("\t'{}': '{}',", key, value))
+                );
+            },
+            _ => panic!("error: invalid query string"),
         }
     }
 
@@ -77,7 +79,7 @@ pub fn profile_for(email: &str) -> String {
 /// "attacker"
 pub fn encrypt(profile: &str) -> Vec<u8> {
     aes128::ecb_encrypt(
-        SOME_KEY,
+        &some_key(),
         profile.as_bytes()
     )
 }
@@ -85,9 +87,9 @@ pub fn encrypt(profile: &str) -> Vec<u8> {
 /// Decrypt the encoded user profile and parse it.
 pub fn decrypt(ciphertext: &[u8]) -> String {
     let plainbytes = aes128::ecb_decrypt(
-        SOME_KEY,
+        &some_key(),
         ciphertext
-    );
+    ).unwrap();
 
     let plaintext = String::from_utf8(plainbytes).unwrap();
 
@@ -116,13 +118,13 @@ pub mod test {
             &challenge13::profile_for(&prefix)
         );
 
-        original_email_block.split_off(16);
+        original_email_block.truncate(16);
 
         let mut previous_email_block = original_email_block.clone();
         let mut current_email_block = Vec::new();
 
         while previous_email_block != current_email_block {
-            prefix.push_str("A");
+            prefix.push('A');
 
             previous_email_block = current_email_block.clone();
             
@@ -130,7 +132,7 @@ pub mod test {
                 &challenge13::profile_for(&prefix)
             );
 
-            current_email_block.split_off(16);
+            current_email_block.truncate(16);
         }
 
         // Adding the last character did not modify the block, nice
@@ -152,7 +154,7 @@ pub mod test {
         );
 
         // Save this for later!
-        let mut encrypted_admin_block = (&ciphertext[16..32]).to_vec();
+        let mut encrypted_admin_block = ciphertext[16..32].to_vec();
 
         //
         // SECOND STEP:
@@ -189,7 +191,7 @@ pub mod test {
         // THIRD STEP:
         // Drop the last block that has the words "user" and replace it with  
         // previously created block that had the words "admin"
-        ciphertext.split_off(
+        ciphertext.truncate(
             ciphertext.len() - 16
         );
 